@@ -0,0 +1,110 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::{Run, Solution};
+
+/// How long to keep running the solution to collect samples, if no number of iterations is given.
+const DEFAULT_TIME_BUDGET: Duration = Duration::from_secs(3);
+
+/// Minimum number of samples to collect, even if that exceeds the time budget.
+const MIN_SAMPLES: usize = 5;
+
+#[derive(Debug, Default)]
+pub(crate) struct BenchOptions {
+    /// Number of iterations to run after warming up. If unset, runs until the time budget is used.
+    pub iterations: Option<usize>,
+    /// How long to keep collecting samples. Defaults to `DEFAULT_TIME_BUDGET`.
+    pub time_budget: Option<Duration>,
+}
+
+/// Summary statistics of the durations of repeated runs.
+#[derive(Debug)]
+pub(crate) struct Stats {
+    pub samples: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Statistics of a single, unbenchmarked run.
+    pub fn single(duration: Duration) -> Self {
+        Self::from_samples(vec![duration])
+    }
+
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let n = samples.len();
+        let mean_nanos = samples.iter().map(|d| d.as_nanos() as f64).sum::<f64>() / n as f64;
+        let variance = samples
+            .iter()
+            .map(|d| (d.as_nanos() as f64 - mean_nanos).powi(2))
+            .sum::<f64>() / (n.max(2) - 1) as f64;
+        Self {
+            samples: n,
+            min: samples[0],
+            median: samples[n / 2],
+            mean: Duration::from_nanos(mean_nanos.round() as u64),
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "min {:.3?}, median {:.3?}, mean {:.3?} ± {:.3?} ({} samples)",
+            self.min, self.median, self.mean, self.stddev, self.samples)
+    }
+}
+
+/// Runs the solution repeatedly on the same input and returns statistics of the durations. The
+/// first tenth of the time budget, and at least one run, is spent warming up (filling caches,
+/// faulting in memory) and is not counted.
+pub(crate) fn bench(solution: &Solution, input: &str, part: Option<usize>, options: &BenchOptions) -> Stats {
+    let time_budget = options.time_budget.unwrap_or(DEFAULT_TIME_BUDGET);
+
+    let warmup_start = Instant::now();
+    loop {
+        run(solution, input, part);
+        if warmup_start.elapsed() >= time_budget / 10 {
+            break;
+        }
+    }
+
+    let mut samples = Vec::new();
+    let start = Instant::now();
+    loop {
+        samples.push(run(solution, input, part).timings.total);
+        let done = match options.iterations {
+            Some(iterations) => samples.len() >= iterations,
+            None => samples.len() >= MIN_SAMPLES && start.elapsed() >= time_budget,
+        };
+        if done {
+            break;
+        }
+    }
+    Stats::from_samples(samples)
+}
+
+/// The input has already been parsed successfully once before benchmarking starts.
+fn run(solution: &Solution, input: &str, part: Option<usize>) -> Run {
+    solution.run(input, part).expect("input should parse")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(
+            [5, 1, 3, 2, 4].into_iter().map(Duration::from_micros).collect());
+        assert_eq!(stats.samples, 5);
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.median, Duration::from_micros(3));
+        assert_eq!(stats.mean, Duration::from_micros(3));
+        assert_eq!(stats.stddev, Duration::from_nanos(1581));
+    }
+}
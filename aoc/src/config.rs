@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+
+use crate::Error;
+
+/// Default config file, relative to the working directory. Can be overridden with `AOC_CONFIG`.
+const CONFIG_FILE_NAME: &str = "aoc.toml";
+
+/// Settings for talking to the Advent of Code server. Read from `aoc.toml` if it exists; each
+/// setting can then be overridden by an environment variable, e.g. `AOC_BASE_URL` for `base_url`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Where to fetch inputs and examples from and submit answers to, without trailing slash.
+    pub base_url: String,
+    pub user_agent: String,
+    /// Timeout for each HTTP request.
+    pub timeout_secs: f64,
+    /// How many times to retry a failed GET request, if the failure looks temporary.
+    pub retries: u32,
+    /// Delay before the first retry. Doubles with each subsequent retry.
+    pub retry_delay_secs: f64,
+    /// Which account to use. Each account has its own inputs, so inputs and answers are stored
+    /// in a subdirectory per profile, e.g. `inputs/alice/05.in`.
+    pub profile: Option<String>,
+    /// File containing the session cookie when no profile is selected.
+    pub session_cookie_file: Option<String>,
+    /// Settings per profile, e.g. `[profiles.alice]`.
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Template for new days created with `aoc new`. Defaults to `template.rs` if it exists.
+    pub template_file: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ProfileConfig {
+    /// File containing this profile's session cookie. Defaults to `.session_cookie.<profile>`.
+    pub session_cookie_file: Option<String>,
+}
+
+static SHARED: OnceCell<Config> = OnceCell::new();
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            base_url: "https://adventofcode.com".to_owned(),
+            user_agent: "aoc2022 by ttencate@gmail.com, https://github.com/ttencate/aoc2022.git".to_owned(),
+            timeout_secs: 30.0,
+            retries: 2,
+            retry_delay_secs: 1.0,
+            profile: None,
+            session_cookie_file: None,
+            profiles: BTreeMap::new(),
+            template_file: None,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the config shared by the whole process, loading it on first use.
+    pub fn shared() -> Result<&'static Self, Error> {
+        SHARED.get_or_try_init(Self::load)
+    }
+
+    /// Returns the name of the file containing the session cookie for the selected profile.
+    pub fn session_cookie_file(&self) -> String {
+        match &self.profile {
+            Some(profile) => self.profiles
+                .get(profile)
+                .and_then(|profile_config| profile_config.session_cookie_file.clone())
+                .unwrap_or_else(|| format!(".session_cookie.{}", profile)),
+            None => self.session_cookie_file
+                .clone()
+                .unwrap_or_else(|| ".session_cookie".to_owned()),
+        }
+    }
+
+    /// Returns the directory for account-specific files of the given kind, such as `inputs`.
+    pub fn profile_dir(&self, dir: &str) -> String {
+        match &self.profile {
+            Some(profile) => format!("{}/{}", dir, profile),
+            None => dir.to_owned(),
+        }
+    }
+
+    fn load() -> Result<Self, Error> {
+        let explicit_file_name = env::var("AOC_CONFIG").ok();
+        let file_name = explicit_file_name.as_deref().unwrap_or(CONFIG_FILE_NAME);
+        let mut config = match fs::read_to_string(file_name) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|err| Error::Config(format!("invalid config file {}: {}", file_name, err)))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && explicit_file_name.is_none() => {
+                Self::default()
+            },
+            Err(err) => return Err(Error::ConfigIo { file_name: file_name.to_owned(), source: err }),
+        };
+        config.apply_env(|name| env::var(name).ok()).map_err(Error::Config)?;
+        config.validate().map_err(Error::Config)?;
+        Ok(config)
+    }
+
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(name: &str, value: String) -> Result<T, String> {
+            value.parse().map_err(|_| format!("invalid value for {}: {:?}", name, value))
+        }
+        if let Some(value) = var("AOC_BASE_URL") {
+            self.base_url = value;
+        }
+        if let Some(value) = var("AOC_USER_AGENT") {
+            self.user_agent = value;
+        }
+        if let Some(value) = var("AOC_TIMEOUT_SECS") {
+            self.timeout_secs = parse("AOC_TIMEOUT_SECS", value)?;
+        }
+        if let Some(value) = var("AOC_RETRIES") {
+            self.retries = parse("AOC_RETRIES", value)?;
+        }
+        if let Some(value) = var("AOC_RETRY_DELAY_SECS") {
+            self.retry_delay_secs = parse("AOC_RETRY_DELAY_SECS", value)?;
+        }
+        if let Some(value) = var("AOC_PROFILE") {
+            self.profile = Some(value);
+        }
+        if let Some(value) = var("AOC_SESSION_COOKIE_FILE") {
+            self.session_cookie_file = Some(value);
+        }
+        if let Some(value) = var("AOC_TEMPLATE_FILE") {
+            self.template_file = Some(value);
+        }
+        Ok(())
+    }
+
+    fn validate(&mut self) -> Result<(), String> {
+        // A zero timeout would make every request fail, but not waiting between retries is fine.
+        if !(self.timeout_secs.is_finite() && self.timeout_secs > 0.0) {
+            return Err(format!("invalid timeout {}", self.timeout_secs));
+        }
+        if !(self.retry_delay_secs.is_finite() && self.retry_delay_secs >= 0.0) {
+            return Err(format!("invalid retry delay {}", self.retry_delay_secs));
+        }
+        if let Some(profile) = &self.profile {
+            // The profile name ends up in file names, so keep it simple.
+            if profile.is_empty() || !profile.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                return Err(format!("invalid profile name {:?}", profile));
+            }
+        }
+        self.base_url = self.base_url.trim_end_matches('/').to_owned();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_and_env() {
+        let mut config = toml::from_str::<Config>("base_url = \"http://mirror/\"\nretries = 5\n").unwrap();
+        config.apply_env(|name| match name {
+            "AOC_RETRIES" => Some("0".to_owned()),
+            "AOC_TIMEOUT_SECS" => Some("2.5".to_owned()),
+            _ => None,
+        }).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.base_url, "http://mirror");
+        assert_eq!(config.retries, 0);
+        assert_eq!(config.timeout_secs, 2.5);
+        assert_eq!(config.user_agent, Config::default().user_agent);
+
+        assert!(config.apply_env(|_| Some("x".to_owned())).is_err());
+        config.timeout_secs = 0.0;
+        assert_eq!(config.validate(), Err("invalid timeout 0".to_owned()));
+        config.timeout_secs = 2.5;
+        config.retry_delay_secs = 0.0;
+        config.validate().unwrap();
+        assert!(toml::from_str::<Config>("bogus = 1").is_err());
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = toml::from_str::<Config>(
+            "session_cookie_file = \"/secrets/aoc\"\n\n[profiles.alice]\nsession_cookie_file = \"/secrets/alice\"\n").unwrap();
+        assert_eq!(config.session_cookie_file(), "/secrets/aoc");
+        assert_eq!(config.profile_dir("inputs"), "inputs");
+        config.profile = Some("alice".to_owned());
+        assert_eq!(config.session_cookie_file(), "/secrets/alice");
+        assert_eq!(config.profile_dir("inputs"), "inputs/alice");
+        config.profile = Some("bob".to_owned());
+        assert_eq!(config.session_cookie_file(), ".session_cookie.bob");
+        config.validate().unwrap();
+        config.profile = Some("../bob".to_owned());
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while getting inputs, examples and answers.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No session cookie was found, and the user could not be asked for one.
+    MissingCookie { file_name: String },
+    /// The server responded with an error status, e.g. 404 for a puzzle that is not unlocked yet.
+    HttpStatus { url: String, status: u16 },
+    /// The request could not be sent, or the response could not be received.
+    Network(Box<dyn StdError + Send + Sync>),
+    /// A cached file, such as an input, could not be read or written.
+    CacheIo { file_name: String, source: io::Error },
+    /// The puzzle page does not have this many examples.
+    ExampleIndexOutOfRange { index: usize, num_examples: usize },
+    /// Something could not be parsed, such as a server response or a cached file.
+    Parse(String),
+    /// The config file or environment variables are invalid.
+    Config(String),
+    /// The config file could not be read.
+    ConfigIo { file_name: String, source: io::Error },
+}
+
+impl Error {
+    pub(crate) fn cache_io(file_name: &str) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Error::CacheIo { file_name: file_name.to_owned(), source }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Error::*;
+        match self {
+            MissingCookie { file_name } => write!(
+                f, "no session cookie found; set the AOC_SESSION environment variable, or put the cookie in {}",
+                file_name),
+            HttpStatus { url, status } => write!(f, "request to {} failed with status {}", url, status),
+            Network(err) => write!(f, "network error: {}", err),
+            CacheIo { file_name, source } => write!(f, "could not access {}: {}", file_name, source),
+            ExampleIndexOutOfRange { index, num_examples } => write!(
+                f, "tried to read example {} but there are only {} examples", index, num_examples),
+            Parse(message) => write!(f, "parse error: {}", message),
+            Config(message) => write!(f, "invalid configuration: {}", message),
+            ConfigIo { file_name, source } => write!(f, "could not read config file {}: {}", file_name, source),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Network(err) => Some(err.as_ref()),
+            Error::CacheIo { source, .. } | Error::ConfigIo { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        match (err.url(), err.status()) {
+            (Some(url), Some(status)) => Error::HttpStatus {
+                url: url.to_string(),
+                status: status.as_u16(),
+            },
+            _ => Error::Network(err.into()),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use kuchiki::traits::TendrilSink;
+use kuchiki::{parse_html, NodeRef};
+use serde::{Deserialize, Serialize};
+
+/// Which examples on a puzzle page are probably inputs, and what the expected answers for them
+/// are. Stored alongside the examples themselves in `examples/NN.toml`.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Manifest {
+    #[serde(default)]
+    pub examples: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub(crate) struct ManifestEntry {
+    /// Index of the example, as passed to `aoc::example!`.
+    pub index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part2: Option<String>,
+}
+
+/// Extracts all `<pre>` blocks from the puzzle page, and guesses which of them are example inputs
+/// and which answers belong to them.
+///
+/// A block is considered an input if the paragraph before it says something like "For example:"
+/// or "Here is a larger example:"; other blocks tend to show intermediate states or output. The
+/// answer to each part is the last emphasized `<code><em>` in that part's `<article>`, and it is
+/// assumed to belong to the most recent input before it.
+pub(crate) fn scrape(html: &str) -> (Vec<String>, Manifest) {
+    let root = parse_html().one(html);
+    let blocks = root.select("pre").unwrap().map(|pre| pre.as_node().clone()).collect::<Vec<_>>();
+
+    let mut entries = BTreeMap::<usize, ManifestEntry>::new();
+    let mut last_input = None;
+    for (part, article) in root.select("article.day-desc").unwrap().take(2).enumerate() {
+        let mut answer = None;
+        for node in article.as_node().select("pre, code > em").unwrap() {
+            let node = node.as_node();
+            if let Some(index) = blocks.iter().position(|block| block == node) {
+                if is_input(node) {
+                    last_input = Some(index);
+                }
+            } else if !node.ancestors().any(|ancestor| is_element(&ancestor, "pre")) {
+                answer = Some((last_input, node.text_contents()));
+            }
+        }
+        if let Some((Some(index), value)) = answer {
+            let entry = entries.entry(index).or_insert_with(|| ManifestEntry { index, ..ManifestEntry::default() });
+            match part {
+                0 => entry.part1 = Some(value),
+                _ => entry.part2 = Some(value),
+            }
+        }
+    }
+
+    let blocks = blocks.iter().map(|block| block.text_contents()).collect();
+    (blocks, Manifest { examples: entries.into_values().collect() })
+}
+
+fn is_input(pre: &NodeRef) -> bool {
+    let Some(intro) = pre.preceding_siblings().find(|sibling| sibling.as_element().is_some()) else {
+        return false;
+    };
+    let intro = intro.text_contents().to_lowercase();
+    ["for example", "larger example", "another example", "following example"]
+        .iter()
+        .any(|phrase| intro.contains(phrase))
+}
+
+fn is_element(node: &NodeRef, name: &str) -> bool {
+    node.as_element().map_or(false, |element| &*element.name.local == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body><main>
+        <article class="day-desc">
+          <h2>--- Day 5: Supply Stacks ---</h2>
+          <p>They do, however, have a drawing:</p>
+          <pre><code>[A]</code></pre>
+          <p>For example:</p>
+          <pre><code>    [D]
+move 1 from 2 to 1
+</code></pre>
+          <p>In this example, the first step moves one crate, resulting in <em>this</em> configuration:</p>
+          <pre><code>[<em>D</em>]</code></pre>
+          <p>In this example, the top crates are <code>C</code>, so you should give <code><em>CMZ</em></code>.</p>
+        </article>
+        <p>Your puzzle answer was <code>VPCDMSLWJ</code>.</p>
+        <article class="day-desc">
+          <h2 id="part2">--- Part Two ---</h2>
+          <p>Now consider a larger example:</p>
+          <pre><code>    [E]
+move 2 from 2 to 1
+</code></pre>
+          <p>In this example, the answer is <code><em>MCD</em></code>.</p>
+        </article>
+    </main></body></html>"#;
+
+    #[test]
+    fn test_scrape() {
+        let (blocks, manifest) = scrape(PAGE);
+        assert_eq!(blocks, vec![
+            "[A]".to_owned(),
+            "    [D]\nmove 1 from 2 to 1\n".to_owned(),
+            "[D]".to_owned(),
+            "    [E]\nmove 2 from 2 to 1\n".to_owned(),
+        ]);
+        assert_eq!(manifest, Manifest {
+            examples: vec![
+                ManifestEntry { index: 1, part1: Some("CMZ".to_owned()), part2: None },
+                ManifestEntry { index: 3, part1: None, part2: Some("MCD".to_owned()) },
+            ],
+        });
+    }
+}
@@ -0,0 +1,190 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::bench::Stats;
+use crate::ensure_dir_exists;
+
+/// File to which timings of all runs are appended, one tab-separated record per line.
+const HISTORY_FILE_NAME: &str = "target/aoc-bench/history.tsv";
+
+/// Timing of a single run of `aoc::main` or of a single day in the runner.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Record {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub year: u32,
+    pub day: u32,
+    /// Output of `git describe --always --dirty`, or `unknown`.
+    pub commit: String,
+    /// `debug` or `release`.
+    pub profile: String,
+    pub samples: usize,
+    pub median: Duration,
+    pub min: Duration,
+}
+
+impl Record {
+    pub fn new(year: u32, day: u32, stats: &Stats) -> Self {
+        Self {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            year,
+            day,
+            commit: git_commit(),
+            profile: if cfg!(debug_assertions) { "debug" } else { "release" }.to_owned(),
+            samples: stats.samples,
+            median: stats.median,
+            min: stats.min,
+        }
+    }
+
+    /// Whether the timings of both records can be meaningfully compared: they are of the same day
+    /// and profile, and either both are single runs or both are medians of a `--bench` run.
+    fn is_comparable(&self, other: &Record) -> bool {
+        self.year == other.year && self.day == other.day && self.profile == other.profile &&
+            (self.samples > 1) == (other.samples > 1)
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.timestamp, self.year, self.day, self.commit, self.profile, self.samples,
+            self.median.as_nanos(), self.min.as_nanos())
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let mut next = || fields.next();
+        let record = Self {
+            timestamp: next()?.parse().ok()?,
+            year: next()?.parse().ok()?,
+            day: next()?.parse().ok()?,
+            commit: next()?.to_owned(),
+            profile: next()?.to_owned(),
+            samples: next()?.parse().ok()?,
+            median: Duration::from_nanos(next()?.parse().ok()?),
+            min: Duration::from_nanos(next()?.parse().ok()?),
+        };
+        Some(record)
+    }
+}
+
+/// Timing differences below this are considered noise, however large they are relatively. This
+/// keeps days that take well under a millisecond from being flagged on every other run.
+const MIN_REGRESSION: Duration = Duration::from_micros(100);
+
+/// Result of comparing a run against the most recent previous run of the same day and profile,
+/// with a comparable number of samples.
+pub(crate) struct Comparison {
+    pub baseline: Record,
+    pub current: Duration,
+    /// Relative change of the median time, e.g. `0.1` means 10% slower.
+    pub change: f64,
+    pub is_regression: bool,
+}
+
+impl Comparison {
+    /// Compares `current` against `baseline`, flagging it as a regression if the median time
+    /// increased by more than `threshold` (as a fraction) and by at least `MIN_REGRESSION`.
+    fn new(baseline: Record, current: &Record, threshold: f64) -> Self {
+        let baseline_median = baseline.median.max(Duration::from_nanos(1));
+        let change = current.median.as_secs_f64() / baseline_median.as_secs_f64() - 1.0;
+        let is_regression =
+            change > threshold && current.median.saturating_sub(baseline.median) >= MIN_REGRESSION;
+        Self { baseline, current: current.median, change, is_regression }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{:.3?} -> {:.3?} ({:+.1}%) since {} ({}){}",
+            self.baseline.median, self.current, self.change * 100.0,
+            self.baseline.commit, self.baseline.profile,
+            if self.is_regression { ", REGRESSION" } else { "" })
+    }
+}
+
+/// Appends the record to the history file. If `threshold` is given, first compares it against the
+/// previous comparable record.
+///
+/// History is a convenience, so I/O errors are reported but otherwise ignored.
+pub(crate) fn record(record: Record, threshold: Option<f64>) -> Option<Comparison> {
+    let comparison = threshold.and_then(|threshold| {
+        let baseline = load()
+            .map_err(|err| eprintln!("Failed to read {}: {}", HISTORY_FILE_NAME, err))
+            .ok()?
+            .into_iter()
+            .rev()
+            .find(|r| r.is_comparable(&record))?;
+        Some(Comparison::new(baseline, &record, threshold))
+    });
+    if let Err(err) = append(&record) {
+        eprintln!("Failed to write {}: {}", HISTORY_FILE_NAME, err);
+    }
+    comparison
+}
+
+fn load() -> Result<Vec<Record>, io::Error> {
+    match fs::read_to_string(HISTORY_FILE_NAME) {
+        Ok(contents) => Ok(contents.lines().filter_map(Record::from_line).collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+fn append(record: &Record) -> Result<(), io::Error> {
+    ensure_dir_exists(HISTORY_FILE_NAME)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(HISTORY_FILE_NAME)?;
+    writeln!(file, "{}", record.to_line())
+}
+
+fn git_commit() -> String {
+    Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_owned())
+        .unwrap_or_else(|| "unknown".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(samples: usize, median: Duration) -> Record {
+        Record {
+            timestamp: 1671000000,
+            year: 2022,
+            day: 12,
+            commit: "3f5ede6-dirty".to_owned(),
+            profile: "release".to_owned(),
+            samples,
+            median,
+            min: Duration::from_nanos(1200000),
+        }
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let record = record(100, Duration::from_nanos(1234567));
+        assert_eq!(Record::from_line(&record.to_line()), Some(record));
+    }
+
+    #[test]
+    fn test_comparison() {
+        let bench = record(100, Duration::from_millis(10));
+        assert!(bench.is_comparable(&record(50, Duration::from_millis(1))));
+        assert!(!bench.is_comparable(&record(1, Duration::from_millis(1))));
+
+        let compare = |before, after| {
+            Comparison::new(record(100, before), &record(100, after), 0.1)
+        };
+        assert!(compare(Duration::from_millis(10), Duration::from_millis(12)).is_regression);
+        assert!(!compare(Duration::from_millis(10), Duration::from_millis(10)).is_regression);
+        let noise = compare(Duration::from_micros(10), Duration::from_micros(30));
+        assert!((noise.change - 2.0).abs() < 1e-9);
+        assert!(!noise.is_regression);
+    }
+}
@@ -0,0 +1,107 @@
+use std::thread;
+use std::time::Duration;
+
+use once_cell::sync::OnceCell;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+use reqwest::header::COOKIE;
+
+use crate::config::Config;
+use crate::Error;
+
+/// HTTP client for the Advent of Code server, or a stand-in for it.
+pub(crate) struct Http {
+    config: Config,
+    client: Client,
+}
+
+static SHARED: OnceCell<Http> = OnceCell::new();
+
+impl Http {
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let client = ClientBuilder::new()
+            .user_agent(&config.user_agent)
+            .timeout(Duration::from_secs_f64(config.timeout_secs))
+            .build()?;
+        Ok(Self { config, client })
+    }
+
+    /// Returns the instance shared by the whole process, configured by `Config::shared`.
+    pub fn shared() -> Result<&'static Self, Error> {
+        SHARED.get_or_try_init(|| Self::new(Config::shared()?.clone()))
+    }
+
+    /// Sends a GET request for the given path, e.g. `/2022/day/1/input`. Timeouts, connection
+    /// errors and server errors are retried according to the config.
+    pub fn get(&self, path: &str, session_cookie: &str) -> Result<Response, Error> {
+        let url = self.url(path);
+        let mut attempt = 0;
+        loop {
+            let result = self.send(self.client.get(&url), session_cookie);
+            match result {
+                Err(err) if attempt < self.config.retries && is_transient(&err) => {
+                    let delay = Duration::from_secs_f64(
+                        self.config.retry_delay_secs * 2.0_f64.powi(attempt as i32));
+                    eprintln!("Request to {} failed ({}), retrying in {:.1?}...", url, err, delay);
+                    thread::sleep(delay);
+                    attempt += 1;
+                },
+                result => return Ok(result?),
+            }
+        }
+    }
+
+    /// Sends a POST request with a URL-encoded form. These are never retried, because they might
+    /// have had an effect even if they appeared to fail.
+    pub fn post_form(&self, path: &str, session_cookie: &str, form: &[(&str, &str)])
+        -> Result<Response, Error>
+    {
+        Ok(self.send(self.client.post(self.url(path)).form(form), session_cookie)?)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.base_url, path)
+    }
+
+    fn send(&self, request: RequestBuilder, session_cookie: &str) -> Result<Response, reqwest::Error> {
+        request
+            .header(COOKIE, format!("session={}", session_cookie))
+            .send()?
+            .error_for_status()
+    }
+}
+
+fn is_transient(err: &reqwest::Error) -> bool {
+    err.is_timeout() ||
+        err.is_connect() ||
+        err.status().map_or(false, |status| status.is_server_error())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_server;
+
+    #[test]
+    fn test_get_retries_server_errors() {
+        let (base_url, server) = test_server::serve(vec![
+            (503, "busy".to_owned()),
+            (200, "1000\n2000\n".to_owned()),
+        ]);
+        let http = Http::new(Config { base_url, retry_delay_secs: 0.0, ..Config::default() }).unwrap();
+        let response = http.get("/2022/day/1/input", "s3cr3t").unwrap();
+        assert_eq!(response.text().unwrap(), "1000\n2000\n");
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|request| request.head[0] == "GET /2022/day/1/input HTTP/1.1"));
+    }
+
+    #[test]
+    fn test_get_gives_up() {
+        let (base_url, server) = test_server::serve(vec![(404, "not found".to_owned())]);
+        let http = Http::new(Config { base_url, ..Config::default() }).unwrap();
+        let err = http.get("/2022/day/26/input", "s3cr3t").unwrap_err();
+        assert!(matches!(err, Error::HttpStatus { status: 404, .. }), "{:?}", err);
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+}
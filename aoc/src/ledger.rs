@@ -0,0 +1,186 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::{ensure_dir_exists, Error};
+use crate::submit::Verdict;
+
+/// Known correct and incorrect answers for a single day, as learned from submissions. Stored in
+/// `answers/NN.toml`, or `answers/<profile>/NN.toml`.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Ledger {
+    #[serde(default, skip_serializing_if = "PartLedger::is_empty")]
+    part1: PartLedger,
+    #[serde(default, skip_serializing_if = "PartLedger::is_empty")]
+    part2: PartLedger,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+struct PartLedger {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    correct: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    too_high: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    too_low: Vec<String>,
+    /// Wrong answers for which the server gave no hint.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    incorrect: Vec<String>,
+}
+
+impl PartLedger {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    fn check(&self, answer: &str) -> Check {
+        if let Some(correct) = &self.correct {
+            return if answer == correct {
+                Check::Matches
+            } else {
+                Check::Regression { expected: correct.clone() }
+            };
+        }
+        if self.too_high.iter().any(|guess| guess == answer) {
+            return Check::KnownWrong("too high".to_owned());
+        }
+        if self.too_low.iter().any(|guess| guess == answer) {
+            return Check::KnownWrong("too low".to_owned());
+        }
+        if self.incorrect.iter().any(|guess| guess == answer) {
+            return Check::KnownWrong("incorrect".to_owned());
+        }
+        if let Ok(answer) = answer.parse::<i128>() {
+            let parse = |guesses: &[String]| guesses
+                .iter()
+                .filter_map(|guess| guess.parse::<i128>().ok())
+                .collect::<Vec<_>>();
+            if let Some(upper) = parse(&self.too_high).into_iter().min().filter(|&upper| answer >= upper) {
+                return Check::KnownWrong(format!("must be less than {}", upper));
+            }
+            if let Some(lower) = parse(&self.too_low).into_iter().max().filter(|&lower| answer <= lower) {
+                return Check::KnownWrong(format!("must be greater than {}", lower));
+            }
+        }
+        Check::Unknown
+    }
+}
+
+/// What the ledger knows about a particular answer.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Check {
+    Unknown,
+    Matches,
+    Regression { expected: String },
+    /// Known to be wrong, either because it was submitted before or because it lies outside the
+    /// bounds given by earlier "too high" or "too low" responses. Contains the reason.
+    KnownWrong(String),
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Check::Unknown => write!(f, "unknown"),
+            Check::Matches => write!(f, "matches known answer"),
+            Check::Regression { expected } => write!(f, "REGRESSION, known answer is {}", expected),
+            Check::KnownWrong(reason) => write!(f, "known to be wrong ({})", reason),
+        }
+    }
+}
+
+impl Ledger {
+    /// Loads the ledger for the given day, or returns an empty one if there is none yet.
+    pub fn load(year: u32, day: u32) -> Result<Self, Error> {
+        let file_name = ledger_file_name(year, day)?;
+        match fs::read_to_string(&file_name) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|err| Error::Parse(format!("{}: {}", file_name, err))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(Error::cache_io(&file_name)(err)),
+        }
+    }
+
+    pub fn save(&self, year: u32, day: u32) -> Result<(), Error> {
+        let file_name = ledger_file_name(year, day)?;
+        let contents = toml::to_string(self).expect("ledger should always be serializable");
+        ensure_dir_exists(&file_name)
+            .and_then(|()| fs::write(&file_name, contents))
+            .map_err(Error::cache_io(&file_name))
+    }
+
+    pub fn check(&self, part: usize, answer: &str) -> Check {
+        self.part(part).check(answer)
+    }
+
+    /// Records the server's verdict on a submitted answer. Verdicts that say nothing about the
+    /// answer itself, such as rate limiting, are ignored.
+    pub fn record(&mut self, part: usize, answer: &str, verdict: &Verdict) {
+        let part = self.part_mut(part);
+        let answer = answer.to_owned();
+        match verdict {
+            Verdict::Correct => part.correct = Some(answer),
+            Verdict::TooHigh => part.too_high.push(answer),
+            Verdict::TooLow => part.too_low.push(answer),
+            Verdict::Incorrect => part.incorrect.push(answer),
+            Verdict::RateLimited(_) | Verdict::AlreadySolved | Verdict::Unknown(_) => {},
+        }
+    }
+
+    fn part(&self, part: usize) -> &PartLedger {
+        match part {
+            1 => &self.part1,
+            2 => &self.part2,
+            _ => panic!("invalid part {}", part),
+        }
+    }
+
+    fn part_mut(&mut self, part: usize) -> &mut PartLedger {
+        match part {
+            1 => &mut self.part1,
+            2 => &mut self.part2,
+            _ => panic!("invalid part {}", part),
+        }
+    }
+}
+
+/// Answers differ per account, so like inputs they are stored per profile.
+fn ledger_file_name(_year: u32, day: u32) -> Result<String, Error> {
+    Ok(format!("{}/{:02}.toml", Config::shared()?.profile_dir("answers"), day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let mut ledger = Ledger::default();
+        assert_eq!(ledger.check(1, "100"), Check::Unknown);
+        ledger.record(1, "200", &Verdict::TooHigh);
+        ledger.record(1, "50", &Verdict::TooLow);
+        ledger.record(1, "123", &Verdict::Incorrect);
+        ledger.record(1, "150", &Verdict::RateLimited(None));
+        assert_eq!(ledger.check(1, "200"), Check::KnownWrong("too high".to_owned()));
+        assert_eq!(ledger.check(1, "123"), Check::KnownWrong("incorrect".to_owned()));
+        assert_eq!(ledger.check(1, "300"), Check::KnownWrong("must be less than 200".to_owned()));
+        assert_eq!(ledger.check(1, "10"), Check::KnownWrong("must be greater than 50".to_owned()));
+        assert_eq!(ledger.check(1, "150"), Check::Unknown);
+        assert_eq!(ledger.check(2, "300"), Check::Unknown);
+        ledger.record(1, "150", &Verdict::Correct);
+        assert_eq!(ledger.check(1, "150"), Check::Matches);
+        assert_eq!(ledger.check(1, "151"), Check::Regression { expected: "150".to_owned() });
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut ledger = Ledger::default();
+        ledger.record(1, "CMZ", &Verdict::Correct);
+        ledger.record(2, "MDC", &Verdict::Incorrect);
+        let toml = toml::to_string(&ledger).unwrap();
+        assert_eq!(toml, "[part1]\ncorrect = \"CMZ\"\n\n[part2]\nincorrect = [\"MDC\"]\n");
+        assert_eq!(toml::from_str::<Ledger>(&toml).unwrap(), ledger);
+    }
+}
@@ -1,19 +1,47 @@
-use std::error::Error;
 use std::fs;
-use std::io::BufRead;
 use std::io;
 use std::path::Path;
-use std::time::Instant;
-
-use kuchiki::parse_html;
-use kuchiki::traits::TendrilSink;
+use std::process;
 
 pub use aoc_proc_macros::*;
 
-/// Generates a `fn main()` implementation. Takes one argument, the run function, which should
-/// accept a string reference to the input and return the puzzle's output. Example:
+pub use error::Error;
+pub use parse::{Parse, ParseError};
+pub use runner::run_all;
+pub use solution::{Run, Solution, SolutionBuilder, Timings};
+
+use bench::Stats;
+use config::Config;
+use examples::Manifest;
+use history::Record;
+use http::Http;
+use ledger::{Check, Ledger};
+use options::{exit_with_usage, Options};
+use session::load_session_cookie;
+use submit::Verdict;
+
+mod bench;
+mod config;
+mod error;
+mod examples;
+mod history;
+mod http;
+mod ledger;
+mod options;
+mod parse;
+mod runner;
+mod scaffold;
+mod session;
+mod solution;
+mod submit;
+#[cfg(test)]
+mod test_server;
+
+/// Generates a `fn main()` implementation. In its simplest form, takes one argument, the run
+/// function, which should accept a string reference to the input and return the puzzle's output.
+/// Example:
 ///
-/// ```
+/// ```ignore
 /// fn run(input: &str) -> (u64, u64) {
 ///     // ...
 /// }
@@ -21,15 +49,175 @@ pub use aoc_proc_macros::*;
 /// aoc::main!(run);
 /// ```
 ///
-/// This needs to be a macro so that it can determine the year from the `CARGO_PKG_NAME` and
-/// `CARGO_BIN_NAME` environment variables, set by Cargo during compilation of the main crate.
+/// Alternatively, each part can have its own function, so that it can be run and timed on its
+/// own, e.g. with `--part 2`. The input is then parsed once by an optional `parse` function, and a
+/// reference to the result is passed to each part. `part2` may be omitted while it's unsolved.
+///
+/// ```ignore
+/// fn parse(input: &str) -> Vec<u64> {
+///     // ...
+/// }
+///
+/// fn part1(numbers: &[u64]) -> u64 {
+///     // ...
+/// }
+///
+/// fn part2(numbers: &[u64]) -> u64 {
+///     // ...
+/// }
+///
+/// aoc::main!(parse = parse, part1 = part1, part2 = part2);
+/// ```
+///
+/// Instead of a parse function, the input type can be given. It must implement `aoc::Parse`, so
+/// that malformed input is reported as an error rather than a panic:
+///
+/// ```ignore
+/// aoc::main!(input_type = Monkeys, part1 = part1, part2 = part2);
+/// ```
+///
+/// Optionally, the expected answers for examples and for the input can be given, and a test is
+/// generated for each of them. Examples are identified by their index, as passed to
+/// `aoc::example!`, and the test is named after it. For example, this generates the tests
+/// `example_0`, `example_3` and `input`:
+///
+/// ```ignore
+/// aoc::main!(run, examples = [(0, (24000, 45000)), (3, (13, 1))], input = (66616, 199172));
+/// ```
+///
+/// This needs to be a macro so that it can determine the year from the `CARGO_PKG_NAME`
+/// environment variable, set by Cargo during compilation of the main crate, and the day from the
+/// name of the source file.
+///
+/// Besides `main`, this also generates a public `solution()` function, so that the file can be
+/// included as a module into the `aoc` runner binary, which runs all days at once.
 #[macro_export]
 macro_rules! main {
-    ($run_fn:expr) => {
+    (
+        @generate $solution:expr, $test_fn:expr
+        $(, examples = [$(($index:tt, $expected:expr)),* $(,)?])?
+        $(, input = $input_expected:expr)?
+        $(,)?
+    ) => {
+        pub fn solution() -> $crate::Solution {
+            $solution
+        }
+
+        #[allow(dead_code)]
         fn main() {
-            $crate::main($crate::year!(), $crate::day!(), $run_fn);
+            $crate::main(solution());
         }
-    }
+
+        $($(
+            $crate::example_test!($index, $test_fn, $expected);
+        )*)?
+
+        $(
+            #[test]
+            fn input() {
+                assert_eq!(($test_fn)(&$crate::input!()), $input_expected);
+            }
+        )?
+    };
+    (
+        parse = $parse_fn:expr, part1 = $part1_fn:expr $(, part2 = $part2_fn:expr)?
+        $(, examples = $examples:tt)? $(, input = $input_expected:expr)? $(,)?
+    ) => {
+        $crate::main!(
+            @generate
+            $crate::Solution::builder($crate::year!(), $crate::day!(), $parse_fn)
+                .part(|parsed| ($part1_fn)(parsed))
+                $(.part(|parsed| ($part2_fn)(parsed)))?
+                .build(),
+            |input: &str| {
+                let parsed = ($parse_fn)(input);
+                (($part1_fn)(&parsed) $(, ($part2_fn)(&parsed))?)
+            }
+            $(, examples = $examples)? $(, input = $input_expected)?
+        );
+    };
+    (
+        input_type = $input_type:ty, part1 = $part1_fn:expr $(, part2 = $part2_fn:expr)?
+        $(, examples = $examples:tt)? $(, input = $input_expected:expr)? $(,)?
+    ) => {
+        $crate::main!(
+            @generate
+            $crate::Solution::parsed::<$input_type>($crate::year!(), $crate::day!())
+                .part(|parsed| ($part1_fn)(parsed))
+                $(.part(|parsed| ($part2_fn)(parsed)))?
+                .build(),
+            |input: &str| {
+                let parsed = <$input_type as $crate::Parse>::parse(input)
+                    .unwrap_or_else(|err| panic!("could not parse input: {}", err));
+                (($part1_fn)(&parsed) $(, ($part2_fn)(&parsed))?)
+            }
+            $(, examples = $examples)? $(, input = $input_expected)?
+        );
+    };
+    (
+        part1 = $part1_fn:expr $(, part2 = $part2_fn:expr)?
+        $(, examples = $examples:tt)? $(, input = $input_expected:expr)? $(,)?
+    ) => {
+        $crate::main!(
+            parse = str::to_owned, part1 = $part1_fn $(, part2 = $part2_fn)?
+            $(, examples = $examples)? $(, input = $input_expected)?
+        );
+    };
+    (
+        $run_fn:expr
+        $(, examples = $examples:tt)? $(, input = $input_expected:expr)? $(,)?
+    ) => {
+        $crate::main!(
+            @generate
+            $crate::Solution::new($crate::year!(), $crate::day!(), $run_fn),
+            $run_fn
+            $(, examples = $examples)? $(, input = $input_expected)?
+        );
+    };
+}
+
+/// Generates a test named `example_N` that checks the answer to example `N`. Used by `aoc::main!`.
+///
+/// Declarative macros cannot construct identifiers, so the supported indices are spelled out.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! example_test {
+    (0, $($args:tt)*) => { $crate::example_test!(@test example_0, 0, $($args)*); };
+    (1, $($args:tt)*) => { $crate::example_test!(@test example_1, 1, $($args)*); };
+    (2, $($args:tt)*) => { $crate::example_test!(@test example_2, 2, $($args)*); };
+    (3, $($args:tt)*) => { $crate::example_test!(@test example_3, 3, $($args)*); };
+    (4, $($args:tt)*) => { $crate::example_test!(@test example_4, 4, $($args)*); };
+    (5, $($args:tt)*) => { $crate::example_test!(@test example_5, 5, $($args)*); };
+    (6, $($args:tt)*) => { $crate::example_test!(@test example_6, 6, $($args)*); };
+    (7, $($args:tt)*) => { $crate::example_test!(@test example_7, 7, $($args)*); };
+    (8, $($args:tt)*) => { $crate::example_test!(@test example_8, 8, $($args)*); };
+    (9, $($args:tt)*) => { $crate::example_test!(@test example_9, 9, $($args)*); };
+    (10, $($args:tt)*) => { $crate::example_test!(@test example_10, 10, $($args)*); };
+    (11, $($args:tt)*) => { $crate::example_test!(@test example_11, 11, $($args)*); };
+    (12, $($args:tt)*) => { $crate::example_test!(@test example_12, 12, $($args)*); };
+    (13, $($args:tt)*) => { $crate::example_test!(@test example_13, 13, $($args)*); };
+    (14, $($args:tt)*) => { $crate::example_test!(@test example_14, 14, $($args)*); };
+    (15, $($args:tt)*) => { $crate::example_test!(@test example_15, 15, $($args)*); };
+    (@test $name:ident, $index:expr, $run_fn:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            assert_eq!(($run_fn)(&$crate::example!($index)), $expected, "example {}", $index);
+        }
+    };
+    ($index:tt, $($args:tt)*) => {
+        compile_error!(concat!("example index out of range for aoc::main!: ", stringify!($index)));
+    };
+}
+
+/// Determines the day from the name of the source file that invoked the macro, e.g. `src/bin/05.rs`
+/// is day 5. Unlike `CARGO_BIN_NAME`, this also works when the file is included as a module into
+/// another binary.
+#[macro_export]
+macro_rules! day {
+    () => {{
+        const DAY: u32 = $crate::day_from_file_name(file!());
+        DAY
+    }}
 }
 
 #[macro_export]
@@ -46,8 +234,41 @@ macro_rules! example {
     }
 }
 
+#[macro_export]
+macro_rules! try_input {
+    () => {
+        $crate::try_input($crate::year!(), $crate::day!())
+    }
+}
+
+#[macro_export]
+macro_rules! try_example {
+    ($idx:expr) => {
+        $crate::try_example($crate::year!(), $crate::day!(), $idx)
+    }
+}
+
+#[macro_export]
+macro_rules! examples {
+    () => {
+        $crate::examples($crate::year!(), $crate::day!())
+    }
+}
+
+#[macro_export]
+macro_rules! try_examples {
+    () => {
+        $crate::try_examples($crate::year!(), $crate::day!())
+    }
+}
+
 pub trait Answer {
     fn show(&self) -> String;
+
+    /// Returns the answer to each part separately.
+    fn parts(&self) -> Vec<String> {
+        vec![self.show()]
+    }
 }
 
 impl Answer for usize {
@@ -76,112 +297,279 @@ impl Answer for String {
             self.to_owned()
         }
     }
+
+    fn parts(&self) -> Vec<String> {
+        vec![self.to_owned()]
+    }
 }
 
 impl<T, U> Answer for (T, U) where T: Answer, U: Answer {
     fn show(&self) -> String {
         format!("Part 1: {}\nPart 2: {}", self.0.show(), self.1.show())
     }
+
+    fn parts(&self) -> Vec<String> {
+        let mut parts = self.0.parts();
+        parts.extend(self.1.parts());
+        parts
+    }
 }
 
-pub fn main<A: Answer, F: FnOnce(&str) -> A>(year: u32, day: u32, run_fn: F) {
-    let input = input(year, day);
+/// Runs the solution on the puzzle input and prints the answer. With the `--bench` command line
+/// option, the solution is then run repeatedly to report more reliable timing statistics.
+///
+/// Answers are checked against the ledger of earlier submissions in `answers/NN.toml`. With
+/// `--submit 1` or `--submit 2`, the answer to that part is submitted to the server, unless the
+/// ledger already knows whether it is right or wrong, and the verdict is added to the ledger.
+///
+/// With `--part 1` or `--part 2`, only that part is run, if the solution has separate parts.
+///
+/// The timing is appended to the benchmark history, unless only one part was run. With
+/// `--compare`, it is compared against the previous run, and the process exits with a nonzero
+/// status if it got slower.
+pub fn main(solution: Solution) {
+    let options = Options::from_args();
+    if options.days.is_some() {
+        exit_with_usage("--days is only supported by the aoc runner");
+    }
+    if options.submit.is_some() && options.bench.is_some() {
+        exit_with_usage("--submit and --bench cannot be combined");
+    }
+
+    let input = try_input(solution.year, solution.day).unwrap_or_else(|err| {
+        eprintln!("Could not get input for {} day {}: {}", solution.year, solution.day, err);
+        process::exit(1);
+    });
 
-    let start = Instant::now();
-    let answer = run_fn(&input);
-    let duration = start.elapsed();
+    let run = solution.run(&input, options.part).unwrap_or_else(|err| {
+        eprintln!("Could not parse input for {} day {}: {}", solution.year, solution.day, err);
+        process::exit(1);
+    });
 
     println!(
-        "Answer to {} day {} ({}.{:03} s):\n{}",
-        year, day, duration.as_secs(), duration.subsec_millis(), answer.show());
+        "Answer to {} day {} ({}):\n{}",
+        solution.year, solution.day, run.timings.show(), run.show());
+
+    let ledger = Ledger::load(solution.year, solution.day)
+        .map_err(|err| eprintln!("Failed to load answer ledger: {}", err))
+        .ok();
+    if let Some(ledger) = &ledger {
+        for (part, part_answer) in &run.answers {
+            let check = ledger.check(*part, part_answer);
+            if check != Check::Unknown {
+                println!("Part {} {}", part, check);
+            }
+        }
+    }
+
+    if let Some(part) = options.submit {
+        let Some(part_answer) = run.answer(part) else {
+            eprintln!("Cannot submit part {}: solution did not produce an answer for it", part);
+            process::exit(1);
+        };
+        if part_answer.contains('\n') {
+            eprintln!("Cannot submit multi-line answer to part {}", part);
+            process::exit(1);
+        }
+        let Some(mut ledger) = ledger else {
+            eprintln!("Not submitting without a readable answer ledger");
+            process::exit(1);
+        };
+        let check = ledger.check(part, part_answer);
+        if check != Check::Unknown {
+            eprintln!("Not submitting {:?} for part {}: {}", part_answer, part, check);
+            process::exit(1);
+        }
+        println!("Submitting {:?} for part {}...", part_answer, part);
+        match submit_answer(solution.year, solution.day, part, part_answer) {
+            Ok(verdict) => {
+                println!("{}", verdict);
+                ledger.record(part, part_answer, &verdict);
+                if let Err(err) = ledger.save(solution.year, solution.day) {
+                    eprintln!("Failed to save answer ledger: {}", err);
+                }
+            },
+            Err(err) => {
+                eprintln!("Submission failed: {}", err);
+                process::exit(1);
+            },
+        }
+    }
+
+    let stats = match &options.bench {
+        Some(bench_options) => {
+            println!("Benchmarking...");
+            let stats = bench::bench(&solution, &input, options.part, bench_options);
+            println!("{}", stats);
+            stats
+        },
+        None => Stats::single(run.timings.total),
+    };
+
+    // Timings of a single part can't be compared to those of a whole run.
+    if options.part.is_some() {
+        return;
+    }
+    let record = Record::new(solution.year, solution.day, &stats);
+    if let Some(comparison) = history::record(record, options.compare) {
+        println!("{}", comparison.describe());
+        if comparison.is_regression {
+            process::exit(1);
+        }
+    }
+}
+
+#[doc(hidden)]
+pub const fn day_from_file_name(file_name: &str) -> u32 {
+    let bytes = file_name.as_bytes();
+    let mut i = bytes.len();
+    while i > 0 && bytes[i - 1] != b'/' && bytes[i - 1] != b'\\' {
+        i -= 1;
+    }
+    while i + 1 < bytes.len() {
+        if bytes[i].is_ascii_digit() && bytes[i + 1].is_ascii_digit() {
+            return ((bytes[i] - b'0') * 10 + (bytes[i + 1] - b'0')) as u32;
+        }
+        i += 1;
+    }
+    panic!("no day found in source file name");
 }
 
+/// Returns the puzzle input for the given day, fetching and caching it if needed. Panics if that
+/// fails; see `try_input` for a version that returns the error instead.
 pub fn input(year: u32, day: u32) -> String {
-    let input_file_name = input_file_name(year, day);
+    try_input(year, day)
+        .unwrap_or_else(|err| panic!("could not get input for {} day {}: {}", year, day, err))
+}
+
+pub fn try_input(year: u32, day: u32) -> Result<String, Error> {
+    let input_file_name = input_file_name(year, day)?;
     fs::read_to_string(&input_file_name)
-        .or_else(|_err| -> Result<String, Box<dyn Error>> {
+        .or_else(|_err| -> Result<String, Error> {
             println!("Input file {} could not be read, fetching...", input_file_name);
             let contents = fetch_input(year, day)?;
-            ensure_dir_exists(&input_file_name)?;
-            fs::write(&input_file_name, &contents)?;
+            ensure_dir_exists(&input_file_name)
+                .and_then(|()| fs::write(&input_file_name, &contents))
+                .map_err(Error::cache_io(&input_file_name))?;
             Ok(contents)
         })
-        .unwrap()
 }
 
+/// Returns the example with the given index from the puzzle page, fetching and caching all
+/// examples if needed. Panics if that fails; see `try_example` for a version that returns the
+/// error instead.
 pub fn example(year: u32, day: u32, index: usize) -> String {
+    try_example(year, day, index)
+        .unwrap_or_else(|err| panic!("could not get example for {} day {}: {}", year, day, err))
+}
+
+pub fn try_example(year: u32, day: u32, index: usize) -> Result<String, Error> {
     let example_file_name = example_file_name(year, day, index);
     fs::read_to_string(&example_file_name)
-        .or_else(|_err| -> Result<String, Box<dyn Error>> {
+        .or_else(|_err| -> Result<String, Error> {
             println!("Example file {} could not be read, fetching...", example_file_name);
             let num_examples = fetch_examples(year, day)?;
             if index >= num_examples {
-                panic!(
-                    "tried to read example {} but there are only {} examples for year {}, day {}",
-                    index, num_examples, year, day);
+                return Err(Error::ExampleIndexOutOfRange { index, num_examples });
             }
-            let contents = fs::read_to_string(&example_file_name)
-                .unwrap();
-            Ok(contents)
+            fs::read_to_string(&example_file_name)
+                .map_err(Error::cache_io(&example_file_name))
+        })
+}
+
+/// An example from the puzzle page, with the expected answers where they could be found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Example {
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// Returns the examples from the puzzle page that are probably inputs, along with the expected
+/// answers to part 1 and part 2 where they could be found. Panics if that fails; see
+/// `try_examples` for a version that returns the error instead.
+///
+/// The pairing is guessed from the text of the puzzle, and recorded in `examples/NN.toml`. If the
+/// guess is wrong, that file can be edited by hand.
+pub fn examples(year: u32, day: u32) -> Vec<Example> {
+    try_examples(year, day)
+        .unwrap_or_else(|err| panic!("could not get examples for {} day {}: {}", year, day, err))
+}
+
+pub fn try_examples(year: u32, day: u32) -> Result<Vec<Example>, Error> {
+    load_manifest(year, day)?
+        .examples
+        .into_iter()
+        .map(|entry| {
+            let input = try_example(year, day, entry.index)?;
+            Ok(Example { input, part1: entry.part1, part2: entry.part2 })
         })
-        .unwrap()
+        .collect()
+}
+
+fn load_manifest(year: u32, day: u32) -> Result<Manifest, Error> {
+    let manifest_file_name = manifest_file_name(year, day);
+    let manifest = match fs::read_to_string(&manifest_file_name) {
+        Ok(contents) => contents,
+        Err(_err) => {
+            println!("Example manifest {} could not be read, fetching...", manifest_file_name);
+            fetch_examples(year, day)?;
+            fs::read_to_string(&manifest_file_name)
+                .map_err(Error::cache_io(&manifest_file_name))?
+        },
+    };
+    toml::from_str::<Manifest>(&manifest)
+        .map_err(|err| Error::Parse(format!("{}: {}", manifest_file_name, err)))
 }
 
-fn input_file_name(_year: u32, day: u32) -> String {
-    format!("inputs/{:02}.in", day)
+/// Inputs differ per account, so they are stored per profile, if a profile is selected.
+fn input_file_name(_year: u32, day: u32) -> Result<String, Error> {
+    Ok(format!("{}/{:02}.in", Config::shared()?.profile_dir("inputs"), day))
 }
 
 fn example_file_name(_year: u32, day: u32, index: usize) -> String {
     format!("examples/{:02}-{}.example", day, index)
 }
 
-fn load_session_cookie() -> Result<String, io::Error> {
-    let cookie_file_name = ".session_cookie";
-    fs::read_to_string(cookie_file_name)
-        .map(|s| s.trim().to_string())
-        .or_else(|_err| -> Result<String, io::Error> {
-            println!("No session cookie found. Please log in to https://adventofcode.com/ in your browser, open the browser console, copy the value of the 'session' cookie, and paste it here:");
-            let mut line = String::new();
-            io::stdin().lock().read_line(&mut line)?;
-            ensure_dir_exists(&cookie_file_name)?;
-            fs::write(&cookie_file_name, &line)?;
-            Ok(line.trim().to_string())
-        })
+fn manifest_file_name(_year: u32, day: u32) -> String {
+    format!("examples/{:02}.toml", day)
 }
 
-fn fetch_input(year: u32, day: u32) -> Result<String, Box<dyn Error>> {
-    let url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
-    send_get_request(&url)?
+fn fetch_input(year: u32, day: u32) -> Result<String, Error> {
+    let path = format!("/{}/day/{}/input", year, day);
+    send_get_request(&path)?
         .text()
         .map_err(From::from)
 }
 
-fn fetch_examples(year: u32, day: u32) -> Result<usize, Box<dyn Error>> {
-    let url = format!("https://adventofcode.com/{}/day/{}", year, day);
-    let mut response = send_get_request(&url)?;
-    let root = parse_html().from_utf8().read_from(&mut response)?;
-    let mut num_examples = 0;
-    for (index, node) in root.select("pre").unwrap().enumerate() {
+/// Writes every `<pre>` block on the puzzle page to its own example file, and the guessed pairing
+/// of inputs and answers to the manifest. Returns the number of examples.
+fn fetch_examples(year: u32, day: u32) -> Result<usize, Error> {
+    let path = format!("/{}/day/{}", year, day);
+    let html = send_get_request(&path)?.text()?;
+    let (blocks, manifest) = examples::scrape(&html);
+    for (index, block) in blocks.iter().enumerate() {
         let file_name = example_file_name(year, day, index);
-        ensure_dir_exists(&file_name)?;
-        fs::write(&file_name, node.text_contents())?;
-        num_examples += 1;
-    }
-    Ok(num_examples)
-}
-
-fn send_get_request(url: &str) -> Result<reqwest::blocking::Response, Box<dyn Error>> {
-    let client = reqwest::blocking::ClientBuilder::new()
-        .user_agent("aoc2022 by ttencate@gmail.com, https://github.com/ttencate/aoc2022.git")
-        .build()
-        .expect("failed to build client");
-    let session_cookie = load_session_cookie()?;
-    let response = client.get(url)
-        .header(reqwest::header::COOKIE, format!("session={}", session_cookie))
-        .send()
-        .expect("request failed")
-        .error_for_status()?;
-    Ok(response)
+        ensure_dir_exists(&file_name)
+            .and_then(|()| fs::write(&file_name, block))
+            .map_err(Error::cache_io(&file_name))?;
+    }
+    let file_name = manifest_file_name(year, day);
+    let contents = toml::to_string(&manifest).expect("manifest should always be serializable");
+    ensure_dir_exists(&file_name)
+        .and_then(|()| fs::write(&file_name, contents))
+        .map_err(Error::cache_io(&file_name))?;
+    Ok(blocks.len())
+}
+
+fn submit_answer(year: u32, day: u32, part: usize, answer: &str) -> Result<Verdict, Error> {
+    let session_cookie = load_session_cookie(Config::shared()?)?;
+    submit::submit_answer(Http::shared()?, &session_cookie, year, day, part, answer)
+}
+
+fn send_get_request(path: &str) -> Result<reqwest::blocking::Response, Error> {
+    let session_cookie = load_session_cookie(Config::shared()?)?;
+    Http::shared()?.get(path, &session_cookie)
 }
 
 fn ensure_dir_exists(file_name: &str) -> Result<(), io::Error> {
@@ -0,0 +1,149 @@
+use std::env;
+use std::process;
+use std::time::Duration;
+
+use crate::bench::BenchOptions;
+use crate::runner::DaySet;
+
+/// Command line options, shared between `aoc::main` and `aoc::run_all`.
+#[derive(Debug, Default)]
+pub(crate) struct Options {
+    /// Which days to run. Only supported by the runner.
+    pub days: Option<DaySet>,
+    /// If set, run each solution repeatedly and report statistics instead of a single timing.
+    pub bench: Option<BenchOptions>,
+    /// If set, compare timings against the previous run and flag increases above this fraction.
+    pub compare: Option<f64>,
+    /// If set, submit the answer to this part (1 or 2) to the server. Not supported by the runner.
+    pub submit: Option<usize>,
+    /// If set, run only this part (1 or 2).
+    pub part: Option<usize>,
+}
+
+/// Default threshold for `--compare`, as a fraction.
+const DEFAULT_THRESHOLD: f64 = 0.1;
+
+pub(crate) const USAGE: &str = "\
+Commands:
+    new DAY [--template FILE]
+                        Create src/bin/DAY.rs from a template and fetch its input (aoc runner only)
+
+Options:
+    --days 1-12,22      Run only the given days (aoc runner only)
+    --part 1|2          Run only the given part, if the solution has separate parts
+    --submit 1|2        Submit the answer to the given part (single day only)
+    --bench             Run the solution repeatedly and report timing statistics
+    --iterations N      With --bench, run exactly N times instead of for a fixed time
+    --time SECONDS      With --bench, run for this long (default 3)
+    --compare           Compare timings against the previous run and flag regressions
+    --threshold PERCENT With --compare, how much slower counts as a regression (default 10)";
+
+impl Options {
+    /// Parses the options from the command line, exiting the process if they are invalid.
+    pub fn from_args() -> Self {
+        Self::parse(env::args().skip(1)).unwrap_or_else(|message| exit_with_usage(&message))
+    }
+
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut bench = false;
+        let mut bench_options = BenchOptions::default();
+        let mut compare = false;
+        let mut threshold = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} requires an argument", arg));
+            match arg.as_str() {
+                "--days" => {
+                    options.days = Some(value()?.parse()?);
+                },
+                "--part" => {
+                    options.part = Some(parse_part(&value()?)?);
+                },
+                "--submit" => {
+                    options.submit = Some(parse_part(&value()?)?);
+                },
+                "--bench" => {
+                    bench = true;
+                },
+                "--iterations" => {
+                    let value = value()?;
+                    let iterations = value.parse()
+                        .map_err(|err| format!("invalid number of iterations {:?}: {}", value, err))?;
+                    if iterations == 0 {
+                        return Err("iterations must be at least 1".to_owned());
+                    }
+                    bench_options.iterations = Some(iterations);
+                },
+                "--time" => {
+                    let value = value()?;
+                    bench_options.time_budget = Some(value.parse::<f64>().ok()
+                        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                        .map(Duration::from_secs_f64)
+                        .ok_or_else(|| format!("invalid time {:?}", value))?);
+                },
+                "--compare" => {
+                    compare = true;
+                },
+                "--threshold" => {
+                    let value = value()?;
+                    threshold = Some(value.parse::<f64>().ok()
+                        .filter(|percent| percent.is_finite())
+                        .map(|percent| percent / 100.0)
+                        .ok_or_else(|| format!("invalid threshold {:?}", value))?);
+                },
+                _ => return Err(format!("unknown argument {:?}", arg)),
+            }
+        }
+        if bench {
+            options.bench = Some(bench_options);
+        } else if bench_options.iterations.is_some() || bench_options.time_budget.is_some() {
+            return Err("--iterations and --time require --bench".to_owned());
+        }
+        if compare {
+            options.compare = Some(threshold.unwrap_or(DEFAULT_THRESHOLD));
+        } else if threshold.is_some() {
+            return Err("--threshold requires --compare".to_owned());
+        }
+        if options.part.is_some() && options.compare.is_some() {
+            return Err("--part and --compare cannot be combined".to_owned());
+        }
+        if let (Some(part), Some(submit)) = (options.part, options.submit) {
+            if part != submit {
+                return Err(format!("cannot submit part {} when only running part {}", submit, part));
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_part(value: &str) -> Result<usize, String> {
+    match value {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        part => Err(format!("invalid part {:?}, must be 1 or 2", part)),
+    }
+}
+
+pub(crate) fn exit_with_usage(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        Options::parse(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn test_iterations() {
+        let options = parse("--bench --iterations 5").unwrap();
+        assert_eq!(options.bench.unwrap().iterations, Some(5));
+        assert_eq!(parse("--bench --iterations 0").err().unwrap(), "iterations must be at least 1");
+        assert!(parse("--bench --iterations -1").is_err());
+        assert!(parse("--iterations 5").is_err());
+    }
+}
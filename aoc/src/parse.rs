@@ -0,0 +1,180 @@
+use std::any::Any;
+use std::error::Error as StdError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A puzzle input type that can be parsed from the input text. Used with
+/// `aoc::main!(input_type = ...)`, so that parsing is timed separately from solving, and malformed
+/// input is reported with its location instead of a panic.
+pub trait Parse: Sized {
+    fn parse(input: &str) -> Result<Self, ParseError>;
+}
+
+/// Parses each line of the input using `FromStr`. If that returns a `ParseError`, it is taken to
+/// be located within the line, and moved to the corresponding location in the input.
+impl<T> Parse for Vec<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display + 'static,
+{
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        input
+            .lines()
+            .map(|line| line.parse().map_err(|err| {
+                match (&err as &dyn Any).downcast_ref::<ParseError>() {
+                    Some(err) => err.clone().relocate(input, line),
+                    None => ParseError::at(input, line, err),
+                }
+            }))
+            .collect()
+    }
+}
+
+/// Where and why the puzzle input could not be parsed. Lines and columns are 1-based, and columns
+/// count characters, not bytes.
+///
+/// If the offending line is known, the error is displayed with that line and a caret pointing at
+/// the column:
+///
+/// ```text
+/// line 3, column 6: expected an integer, found "x"
+///   |
+/// 3 | addx x
+///   |      ^
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    /// The text of the offending line, without line terminator.
+    pub excerpt: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl fmt::Display) -> Self {
+        Self { line, column, message: message.to_string(), excerpt: None }
+    }
+
+    /// Creates an error located at the start of `location`, which must be a slice of `input`,
+    /// e.g. the line or token that failed to parse. If it's not, the error is located at the end
+    /// of the input.
+    pub fn at(input: &str, location: &str, message: impl fmt::Display) -> Self {
+        let offset = offset_in(input, location);
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..].find('\n').map_or(input.len(), |i| offset + i);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.to_string(),
+            excerpt: Some(input[line_start..line_end].trim_end_matches('\r').to_owned()),
+        }
+    }
+
+    /// Like `at`, with a message saying what was expected and what was found instead, e.g.
+    /// `expected an integer, found "x"`.
+    pub fn expected(input: &str, location: &str, expected: impl fmt::Display) -> Self {
+        let rest = &input[offset_in(input, location)..];
+        let found = if rest.is_empty() {
+            "end of input".to_owned()
+        } else if rest.starts_with('\n') || rest.starts_with("\r\n") {
+            "end of line".to_owned()
+        } else if rest.starts_with(char::is_whitespace) {
+            "whitespace".to_owned()
+        } else {
+            format!("{:?}", rest.split(char::is_whitespace).next().unwrap())
+        };
+        Self::at(input, location, format!("expected {}, found {}", expected, found))
+    }
+
+    /// Moves an error that was located within `slice` to the corresponding location in `input`,
+    /// of which `slice` must be a part. Useful when parsing the input piecewise, e.g. line by line.
+    pub fn relocate(self, input: &str, slice: &str) -> Self {
+        let start = Self::at(input, slice, "");
+        let (line, column) = if self.line == 1 {
+            (start.line, start.column + self.column - 1)
+        } else {
+            (start.line + self.line - 1, self.column)
+        };
+        let excerpt = input.lines().nth(line - 1).map(|line| line.trim_end_matches('\r').to_owned());
+        Self { line, column, message: self.message, excerpt: excerpt.or(self.excerpt) }
+    }
+}
+
+/// Returns the byte offset of `location` in `input`, or the length of `input` if `location` is
+/// not part of it.
+fn offset_in(input: &str, location: &str) -> usize {
+    (location.as_ptr() as usize)
+        .checked_sub(input.as_ptr() as usize)
+        .filter(|&offset| offset <= input.len() && input.is_char_boundary(offset))
+        .unwrap_or(input.len())
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)?;
+        if let Some(excerpt) = &self.excerpt {
+            let gutter = " ".repeat(self.line.to_string().len());
+            write!(f, "\n{} |", gutter)?;
+            write!(f, "\n{} | {}", self.line, excerpt)?;
+            write!(f, "\n{} | {:>width$}", gutter, "^", width = self.column)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn located(line: usize, column: usize, message: &str, excerpt: &str) -> ParseError {
+        ParseError { excerpt: Some(excerpt.to_owned()), ..ParseError::new(line, column, message) }
+    }
+
+    #[test]
+    fn test_at() {
+        let input = "noop\naddx 3\naddx é\n";
+        let line = input.lines().nth(2).unwrap();
+        assert_eq!(ParseError::at(input, input, "x"), located(1, 1, "x", "noop"));
+        assert_eq!(ParseError::at(input, &line[5..], "x"), located(3, 6, "x", "addx é"));
+        assert_eq!(ParseError::at(input, &input[input.len()..], "x"), located(4, 1, "x", ""));
+        assert_eq!(ParseError::at(input, "elsewhere", "x"), located(4, 1, "x", ""));
+    }
+
+    #[test]
+    fn test_expected() {
+        let input = "addx 3\naddx x y\naddx\n";
+        let message = |location| ParseError::expected(input, location, "an integer").message;
+        assert_eq!(message(&input[12..]), "expected an integer, found \"x\"");
+        assert_eq!(message(&input[11..]), "expected an integer, found whitespace");
+        assert_eq!(message(&input[20..]), "expected an integer, found end of line");
+        assert_eq!(message(&input[21..]), "expected an integer, found end of input");
+    }
+
+    #[test]
+    fn test_relocate() {
+        let input = "noop\naddx x\n";
+        let line = input.lines().nth(1).unwrap();
+        let err = ParseError::expected(line, &line[5..], "an integer").relocate(input, line);
+        assert_eq!(err, located(2, 6, "expected an integer, found \"x\"", "addx x"));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            located(3, 6, "expected an integer, found \"x\"", "addx x").to_string(),
+            "line 3, column 6: expected an integer, found \"x\"\n  |\n3 | addx x\n  |      ^");
+        assert_eq!(ParseError::new(3, 6, "oops").to_string(), "line 3, column 6: oops");
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(Vec::<u32>::parse("1\n2\n"), Ok(vec![1, 2]));
+        assert_eq!(
+            Vec::<u32>::parse("1\nx\n"),
+            Err(located(2, 1, "invalid digit found in string", "x")));
+    }
+}
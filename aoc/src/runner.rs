@@ -0,0 +1,195 @@
+use std::env;
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::bench::{self, Stats};
+use crate::history::{self, Comparison, Record};
+use crate::options::{exit_with_usage, Options};
+use crate::scaffold;
+use crate::{try_input, Solution};
+
+/// Runs all given solutions in order, then prints a summary table of their answers and timings.
+/// Exits with a nonzero status if any of them failed, either because the input could not be
+/// obtained or because the solution panicked.
+///
+/// Accepts a `--days` command line argument to select which days to run, e.g. `--days 1-12,22`.
+/// With `--bench`, each day is benchmarked and the table shows the median time. With `--compare`,
+/// timings are compared against the previous run and regressions also cause a nonzero exit status.
+/// With `--part`, only that part of each day is run, and timings are not recorded.
+///
+/// Alternatively, `new DAY` creates a new day from the template instead of running anything.
+pub fn run_all(year: u32, solutions: &[Solution]) {
+    if env::args().nth(1).as_deref() == Some("new") {
+        scaffold::new_day(year, env::args().skip(2));
+        return;
+    }
+
+    let options = Options::from_args();
+    if options.submit.is_some() {
+        exit_with_usage("--submit is only supported when running a single day");
+    }
+    let days = options.days.as_ref().map_or_else(DaySet::all, |days| days.clone());
+
+    let results = solutions
+        .iter()
+        .filter(|solution| days.contains(solution.day))
+        .map(|solution| (solution, run_one(solution, &options)))
+        .collect::<Vec<_>>();
+
+    println!();
+    print_table(&results);
+
+    let num_failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if num_failed > 0 {
+        eprintln!("{} of {} days failed", num_failed, results.len());
+    }
+    let num_regressions = print_comparisons(&results);
+    if num_regressions > 0 {
+        eprintln!("{} of {} days got slower", num_regressions, results.len());
+    }
+    if num_failed > 0 || num_regressions > 0 {
+        process::exit(1);
+    }
+}
+
+struct DayResult {
+    /// Answers to part 1 and 2; empty if the part was not run.
+    parts: [String; 2],
+    stats: Stats,
+    comparison: Option<Comparison>,
+}
+
+type RunResult = Result<DayResult, String>;
+
+fn run_one(solution: &Solution, options: &Options) -> RunResult {
+    println!("Running {} day {}...", solution.year, solution.day);
+    let input = try_input(solution.year, solution.day)
+        .map_err(|err| format!("could not get input: {}", err))?;
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let run = solution.run(&input, options.part)
+            .map_err(|err| format!("could not parse input: {}", err))?;
+        let stats = match &options.bench {
+            Some(bench_options) => bench::bench(solution, &input, options.part, bench_options),
+            None => Stats::single(run.timings.total),
+        };
+        let comparison = if options.part.is_none() {
+            history::record(Record::new(solution.year, solution.day, &stats), options.compare)
+        } else {
+            None
+        };
+        let parts = [1, 2].map(|part| run.answer(part).unwrap_or("").to_owned());
+        Ok(DayResult { parts, stats, comparison })
+    }))
+    .map_err(|payload| {
+        let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_owned());
+        format!("panicked: {}", message)
+    })
+    .and_then(|result| result)
+}
+
+fn print_table(results: &[(&Solution, RunResult)]) {
+    let mut total = Duration::ZERO;
+    println!("{:>4}  {:>10}  {:<20}  Part 2", "Day", "Time", "Part 1");
+    for (solution, result) in results {
+        match result {
+            Ok(DayResult { parts, stats, .. }) => {
+                let duration = stats.median;
+                total += duration;
+                // Multi-line answers don't fit in the table, so they are printed below the row.
+                let cells = parts
+                    .iter()
+                    .map(|part| if part.contains('\n') { "(see below)" } else { part })
+                    .collect::<Vec<_>>();
+                println!(
+                    "{:>4}  {:>10}  {:<20}  {}",
+                    solution.day,
+                    format!("{:.3?}", duration),
+                    cells[0],
+                    cells[1]);
+                for part in parts.iter().filter(|part| part.contains('\n')) {
+                    for line in part.lines() {
+                        println!("{:18}{}", "", line);
+                    }
+                }
+            },
+            Err(message) => {
+                println!("{:>4}  {:>10}  {}", solution.day, "FAILED", message);
+            },
+        }
+    }
+    println!("{:>4}  {:>10}", "", format!("{:.3?}", total));
+}
+
+/// Prints the comparisons against previous runs, if any, and returns the number of regressions.
+fn print_comparisons(results: &[(&Solution, RunResult)]) -> usize {
+    let comparisons = results
+        .iter()
+        .filter_map(|(solution, result)| Some((solution, result.as_ref().ok()?.comparison.as_ref()?)))
+        .collect::<Vec<_>>();
+    if !comparisons.is_empty() {
+        println!();
+        println!("Compared to previous runs:");
+        for (solution, comparison) in &comparisons {
+            println!("{:>4}  {}", solution.day, comparison.describe());
+        }
+    }
+    comparisons.iter().filter(|(_, comparison)| comparison.is_regression).count()
+}
+
+/// A set of days, parsed from a comma-separated list of days and inclusive ranges of days, e.g.
+/// `1-12,22`.
+#[derive(Clone, Debug)]
+pub(crate) struct DaySet(Vec<RangeInclusive<u32>>);
+
+impl DaySet {
+    fn all() -> Self {
+        Self(vec![1..=25])
+    }
+
+    fn contains(&self, day: u32) -> bool {
+        self.0.iter().any(|range| range.contains(&day))
+    }
+}
+
+impl FromStr for DaySet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_day = |day: &str| day.trim().parse::<u32>()
+            .map_err(|err| format!("invalid day {:?}: {}", day, err));
+        s.split(',')
+            .map(|range| match range.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse_day(start)?, parse_day(end)?);
+                    if start > end {
+                        return Err(format!("invalid range {:?}: {} is after {}", range, start, end));
+                    }
+                    Ok(start..=end)
+                },
+                None => parse_day(range).map(|day| day..=day),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(DaySet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_set() {
+        let days = "1-12,22".parse::<DaySet>().unwrap();
+        assert!(days.contains(1));
+        assert!(days.contains(12));
+        assert!(!days.contains(13));
+        assert!(days.contains(22));
+        assert!("1-x".parse::<DaySet>().is_err());
+        assert_eq!("12-1".parse::<DaySet>().err().unwrap(), r#"invalid range "12-1": 12 is after 1"#);
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+
+use crate::config::Config;
+use crate::options::exit_with_usage;
+use crate::{load_manifest, try_input};
+
+/// Used if no template file is configured and `template.rs` does not exist.
+const DEFAULT_TEMPLATE: &str = include_str!("../template.rs");
+
+/// Template file used if it exists and no other file is configured.
+const DEFAULT_TEMPLATE_FILE: &str = "template.rs";
+
+/// Implements `aoc new DAY [--template FILE]`: creates `src/bin/NN.rs` from the template and fetches
+/// the input and examples, so that the new day is ready to run. Exits the process on failure.
+pub(crate) fn new_day(year: u32, args: impl IntoIterator<Item = String>) {
+    let (day, template_file) = parse_args(args).unwrap_or_else(|message| exit_with_usage(&message));
+    if let Err(message) = scaffold(year, day, template_file.as_deref()) {
+        eprintln!("Could not create day {}: {}", day, message);
+        process::exit(1);
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<(u32, Option<String>), String> {
+    let mut day = None;
+    let mut template_file = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--template" => {
+                template_file = Some(args.next().ok_or("--template requires an argument")?);
+            },
+            _ if day.is_none() => {
+                day = Some(arg.parse::<u32>().ok()
+                    .filter(|day| (1..=25).contains(day))
+                    .ok_or_else(|| format!("invalid day {:?}", arg))?);
+            },
+            _ => return Err(format!("unknown argument {:?}", arg)),
+        }
+    }
+    Ok((day.ok_or("new requires a day")?, template_file))
+}
+
+fn scaffold(year: u32, day: u32, template_file: Option<&str>) -> Result<(), String> {
+    let file_name = format!("src/bin/{:02}.rs", day);
+    if Path::new(&file_name).exists() {
+        return Err(format!("{} already exists", file_name));
+    }
+    let template = load_template(template_file)?;
+
+    // Fetch the input now, so that it's there when we're ready to run. Failure is not fatal: the
+    // puzzle may not be unlocked yet, and fetching is retried on first run anyway.
+    if let Err(err) = try_input(year, day) {
+        eprintln!("Could not fetch input: {}", err);
+    }
+    let example = match load_manifest(year, day) {
+        Ok(manifest) => manifest.examples.first().map_or(0, |entry| entry.index),
+        Err(err) => {
+            eprintln!("Could not fetch examples: {}", err);
+            0
+        },
+    };
+
+    let contents = render(&template, year, day, example);
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&file_name)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|err| format!("could not write {}: {}", file_name, err))?;
+    println!("Created {}", file_name);
+    Ok(())
+}
+
+fn load_template(template_file: Option<&str>) -> Result<String, String> {
+    let configured = match template_file {
+        Some(template_file) => Some(template_file.to_owned()),
+        None => Config::shared().map_err(|err| err.to_string())?.template_file.clone(),
+    };
+    let file_name = configured.as_deref().unwrap_or(DEFAULT_TEMPLATE_FILE);
+    match fs::read_to_string(file_name) {
+        Ok(template) => Ok(template),
+        Err(err) if err.kind() == io::ErrorKind::NotFound && configured.is_none() => {
+            Ok(DEFAULT_TEMPLATE.to_owned())
+        },
+        Err(err) => Err(format!("could not read template {}: {}", file_name, err)),
+    }
+}
+
+/// Substitutes `{{year}}`, `{{day}}` (zero-padded to two digits) and `{{example}}` (the index of
+/// the first example that looks like an input) in the template.
+fn render(template: &str, year: u32, day: u32, example: usize) -> String {
+    template
+        .replace("{{year}}", &year.to_string())
+        .replace("{{day}}", &format!("{:02}", day))
+        .replace("{{example}}", &example.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+        assert_eq!(args(&["5"]), Ok((5, None)));
+        assert_eq!(args(&["--template", "t.rs", "25"]), Ok((25, Some("t.rs".to_owned()))));
+        assert!(args(&[]).is_err());
+        assert!(args(&["26"]).is_err());
+        assert!(args(&["5", "6"]).is_err());
+        assert!(args(&["5", "--template"]).is_err());
+    }
+
+    #[test]
+    fn test_default_template() {
+        let template = render(DEFAULT_TEMPLATE, 2022, 7, 1);
+        assert!(template.contains("fn part1(_input: &str)"));
+        assert!(template.contains("examples = [(1, 0)]"));
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("// {{year}} day {{day}}\naoc::main!(part1 = part1, examples = [({{example}}, 0)]);\n", 2022, 7, 1),
+            "// 2022 day 07\naoc::main!(part1 = part1, examples = [(1, 0)]);\n");
+    }
+}
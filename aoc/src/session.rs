@@ -0,0 +1,35 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+
+use is_terminal::IsTerminal;
+
+use crate::config::Config;
+use crate::{ensure_dir_exists, Error};
+
+/// Returns the session cookie to authenticate with. Taken from the `AOC_SESSION` environment
+/// variable if set, otherwise from the session cookie file of the selected profile. If that file
+/// does not exist, the user is asked for the cookie, but only if stdin is a terminal; otherwise
+/// this would hang CI and scripted runs.
+pub(crate) fn load_session_cookie(config: &Config) -> Result<String, Error> {
+    if let Ok(cookie) = env::var("AOC_SESSION") {
+        return Ok(cookie.trim().to_string());
+    }
+    let cookie_file_name = config.session_cookie_file();
+    match fs::read_to_string(&cookie_file_name) {
+        Ok(cookie) => Ok(cookie.trim().to_string()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            if !io::stdin().is_terminal() {
+                return Err(Error::MissingCookie { file_name: cookie_file_name });
+            }
+            println!("No session cookie found in {}. Please log in to https://adventofcode.com/ in your browser, open the browser console, copy the value of the 'session' cookie, and paste it here:", cookie_file_name);
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line).map_err(Error::cache_io("<stdin>"))?;
+            ensure_dir_exists(&cookie_file_name)
+                .and_then(|()| fs::write(&cookie_file_name, &line))
+                .map_err(Error::cache_io(&cookie_file_name))?;
+            Ok(line.trim().to_string())
+        },
+        Err(err) => Err(Error::cache_io(&cookie_file_name)(err)),
+    }
+}
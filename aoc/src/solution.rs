@@ -0,0 +1,234 @@
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::{Answer, Parse, ParseError};
+
+/// The solution to a single day, as generated by `aoc::main!`.
+///
+/// A solution is either a single run function that computes all answers at once, or a parse
+/// function followed by a separate function for each part. Only the latter can run and time each
+/// part on its own.
+pub struct Solution {
+    pub year: u32,
+    pub day: u32,
+    run_fn: RunFn,
+}
+
+/// Runs a solution on an input, running only the given part if any.
+type RunFn = Box<dyn Fn(&str, Option<usize>) -> Result<Run, ParseError>>;
+
+/// The answers produced by a single run of a solution, and how long it took.
+pub struct Run {
+    /// The answer to each part that was run, along with its number (1 or 2).
+    pub answers: Vec<(usize, String)>,
+    pub timings: Timings,
+}
+
+/// How long a single run of a solution took.
+#[derive(Debug, Default)]
+pub struct Timings {
+    /// Time spent parsing the input, if the solution has a separate parse step.
+    pub parse: Option<Duration>,
+    /// Time spent on each part that was run, if the solution has separate parts.
+    pub parts: Vec<(usize, Duration)>,
+    /// Time spent in total, including parsing.
+    pub total: Duration,
+}
+
+impl Solution {
+    /// Creates a solution from a single function that returns the answers to all parts at once.
+    /// If only one part is requested, the others are still computed but not reported.
+    pub fn new<A, F>(year: u32, day: u32, run_fn: F) -> Self
+    where
+        A: Answer + 'static,
+        F: Fn(&str) -> A + 'static,
+    {
+        Self {
+            year,
+            day,
+            run_fn: Box::new(move |input, part| {
+                let start = Instant::now();
+                let answer = run_fn(input);
+                let total = start.elapsed();
+                let answers = answer
+                    .parts()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, answer)| (i + 1, answer))
+                    .filter(|(number, _)| part.map_or(true, |part| part == *number))
+                    .collect();
+                Ok(Run { answers, timings: Timings { total, ..Timings::default() } })
+            }),
+        }
+    }
+
+    /// Starts building a solution that first parses the input using the given function, then
+    /// passes the result to each part separately.
+    pub fn builder<P, F>(year: u32, day: u32, parse_fn: F) -> SolutionBuilder<P>
+    where
+        F: Fn(&str) -> P + 'static,
+    {
+        SolutionBuilder {
+            year,
+            day,
+            parse_fn: Box::new(move |input| Ok(parse_fn(input))),
+            part_fns: Vec::new(),
+        }
+    }
+
+    /// Like `builder`, but parses the input using its `Parse` implementation, which may fail.
+    pub fn parsed<P: Parse + 'static>(year: u32, day: u32) -> SolutionBuilder<P> {
+        SolutionBuilder { year, day, parse_fn: Box::new(P::parse), part_fns: Vec::new() }
+    }
+
+    /// Runs the solution on the given input. If `part` is given, only that part is run. Fails only
+    /// if the solution has a `Parse` step and the input is malformed.
+    pub fn run(&self, input: &str, part: Option<usize>) -> Result<Run, ParseError> {
+        (self.run_fn)(input, part)
+    }
+}
+
+/// Builds a `Solution` with a separate parse step and separate parts.
+pub struct SolutionBuilder<P> {
+    year: u32,
+    day: u32,
+    parse_fn: ParseFn<P>,
+    part_fns: Vec<PartFn<P>>,
+}
+
+type ParseFn<P> = Box<dyn Fn(&str) -> Result<P, ParseError>>;
+
+/// Computes the answer to a part from the parsed input, formatted as a string.
+type PartFn<P> = Box<dyn Fn(&P) -> String>;
+
+impl<P: 'static> SolutionBuilder<P> {
+    /// Adds the next part; the first call adds part 1, the second part 2.
+    pub fn part<A, F>(mut self, part_fn: F) -> Self
+    where
+        A: Answer + 'static,
+        F: Fn(&P) -> A + 'static,
+    {
+        self.part_fns.push(Box::new(move |parsed| part_fn(parsed).parts().join("\n")));
+        self
+    }
+
+    pub fn build(self) -> Solution {
+        let Self { year, day, parse_fn, part_fns } = self;
+        Solution {
+            year,
+            day,
+            run_fn: Box::new(move |input, part| {
+                let start = Instant::now();
+                let parsed = parse_fn(input)?;
+                let mut timings = Timings { parse: Some(start.elapsed()), ..Timings::default() };
+                let mut answers = Vec::new();
+                for (i, part_fn) in part_fns.iter().enumerate() {
+                    let number = i + 1;
+                    if part.map_or(true, |part| part == number) {
+                        let part_start = Instant::now();
+                        answers.push((number, part_fn(&parsed)));
+                        timings.parts.push((number, part_start.elapsed()));
+                    }
+                }
+                timings.total = start.elapsed();
+                Ok(Run { answers, timings })
+            }),
+        }
+    }
+}
+
+impl Run {
+    /// Returns the answer to the given part, if it was run.
+    pub fn answer(&self, part: usize) -> Option<&str> {
+        self.answers
+            .iter()
+            .find(|(number, _)| *number == part)
+            .map(|(_, answer)| answer.as_str())
+    }
+
+    /// Formats the answers one per line, e.g. `Part 1: 42`. Multi-line answers start on a new
+    /// line.
+    pub fn show(&self) -> String {
+        let mut out = String::new();
+        for (number, answer) in &self.answers {
+            let separator = if answer.contains('\n') { "\n" } else { " " };
+            writeln!(out, "Part {}:{}{}", number, separator, answer).unwrap();
+        }
+        out.pop();
+        out
+    }
+}
+
+impl Timings {
+    /// Formats the total time, followed by the time per step if there are separate steps, e.g.
+    /// `12.000ms; parse 2.000ms, part 1 10.000ms`.
+    pub fn show(&self) -> String {
+        let mut out = format!("{:.3?}", self.total);
+        let steps = self.parse
+            .map(|parse| ("parse".to_owned(), parse))
+            .into_iter()
+            .chain(self.parts.iter().map(|(number, duration)| (format!("part {}", number), *duration)))
+            .map(|(name, duration)| format!("{} {:.3?}", name, duration))
+            .collect::<Vec<_>>();
+        if !steps.is_empty() {
+            write!(out, "; {}", steps.join(", ")).unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole() {
+        let solution = Solution::new(2022, 1, |input: &str| (input.len(), input.to_owned()));
+        let run = solution.run("ab", None).unwrap();
+        assert_eq!(run.answers, vec![(1, "2".to_owned()), (2, "ab".to_owned())]);
+        assert_eq!(run.show(), "Part 1: 2\nPart 2: ab");
+        assert!(run.timings.parse.is_none());
+        assert_eq!(solution.run("ab", Some(2)).unwrap().answers, vec![(2, "ab".to_owned())]);
+    }
+
+    #[test]
+    fn test_parts() {
+        let solution = Solution::builder(2022, 1, |input: &str| input.lines().count())
+            .part(|lines: &usize| *lines)
+            .part(|lines: &usize| format!("{}\n{}", lines, lines))
+            .build();
+        let run = solution.run("a\nb\n", None).unwrap();
+        assert_eq!(run.answer(1), Some("2"));
+        assert_eq!(run.show(), "Part 1: 2\nPart 2:\n2\n2");
+        assert!(run.timings.parse.is_some());
+        assert_eq!(run.timings.parts.len(), 2);
+
+        let run = solution.run("a\nb\n", Some(2)).unwrap();
+        assert_eq!(run.answer(1), None);
+        assert_eq!(run.timings.parts.iter().map(|(number, _)| *number).collect::<Vec<_>>(), vec![2]);
+        assert!(run.timings.parse.is_some());
+        assert!(run.timings.show().contains("; parse "));
+        assert!(run.timings.show().contains(", part 2 "));
+    }
+
+    #[test]
+    fn test_show_timings() {
+        let timings = Timings {
+            parse: Some(Duration::from_micros(2500)),
+            parts: vec![(1, Duration::from_millis(10)), (2, Duration::from_nanos(1234))],
+            total: Duration::from_secs(2),
+        };
+        assert_eq!(timings.show(), "2.000s; parse 2.500ms, part 1 10.000ms, part 2 1.234µs");
+        let timings = Timings { total: Duration::from_millis(12), ..Timings::default() };
+        assert_eq!(timings.show(), "12.000ms");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let solution = Solution::parsed(2022, 1)
+            .part(|numbers: &Vec<u32>| numbers.iter().sum::<u32>() as usize)
+            .build();
+        assert_eq!(solution.run("1\n2\n", None).unwrap().answer(1), Some("3"));
+        assert_eq!(solution.run("1\n2x\n", None).err().unwrap().line, 2);
+    }
+}
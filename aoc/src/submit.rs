@@ -0,0 +1,154 @@
+use std::fmt;
+use std::time::Duration;
+
+use kuchiki::parse_html;
+use kuchiki::traits::TendrilSink;
+
+use crate::http::Http;
+use crate::Error;
+
+/// The server's response to a submitted answer.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, but the server didn't say in which direction.
+    Incorrect,
+    /// An answer was submitted too recently. Contains the remaining wait time, if it could be
+    /// parsed from the response.
+    RateLimited(Option<Duration>),
+    /// The part was already solved, or part 2 was submitted before part 1 was solved.
+    AlreadySolved,
+    /// The response could not be interpreted; contains its text.
+    Unknown(String),
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Verdict::*;
+        match self {
+            Correct => write!(f, "Correct!"),
+            TooHigh => write!(f, "Wrong: too high"),
+            TooLow => write!(f, "Wrong: too low"),
+            Incorrect => write!(f, "Wrong"),
+            RateLimited(Some(wait)) => write!(f, "Rate limited; try again in {}s", wait.as_secs()),
+            RateLimited(None) => write!(f, "Rate limited; try again later"),
+            AlreadySolved => write!(f, "Already solved, or wrong level"),
+            Unknown(text) => write!(f, "Unrecognized response: {}", text),
+        }
+    }
+}
+
+/// POSTs the answer to the given part to the server and interprets the response.
+pub(crate) fn submit_answer(
+    http: &Http, session_cookie: &str, year: u32, day: u32, part: usize, answer: &str)
+    -> Result<Verdict, Error>
+{
+    let path = format!("/{}/day/{}/answer", year, day);
+    let response = http.post_form(
+        &path, session_cookie, &[("level", part.to_string().as_str()), ("answer", answer)])?;
+    Ok(parse_verdict(&response.text()?))
+}
+
+fn parse_verdict(html: &str) -> Verdict {
+    let root = parse_html().one(html);
+    let text = root
+        .select_first("article")
+        .map(|article| article.text_contents())
+        .unwrap_or_else(|_| root.text_contents());
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.contains("That's the right answer") {
+        Verdict::Correct
+    } else if text.contains("That's not the right answer") {
+        if text.contains("your answer is too high") {
+            Verdict::TooHigh
+        } else if text.contains("your answer is too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Incorrect
+        }
+    } else if text.contains("You gave an answer too recently") {
+        Verdict::RateLimited(parse_wait_time(&text))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Verdict::AlreadySolved
+    } else {
+        Verdict::Unknown(text)
+    }
+}
+
+/// Parses e.g. "You have 1m 5s left to wait."
+fn parse_wait_time(text: &str) -> Option<Duration> {
+    let start = text.find("You have ")? + "You have ".len();
+    let end = start + text[start..].find(" left to wait")?;
+    let mut secs = 0;
+    for part in text[start..end].split(' ') {
+        let (i, unit) = part.char_indices().next_back()?;
+        let number = part[..i].parse::<u64>().ok()?;
+        secs += match unit {
+            'h' => number * 3600,
+            'm' => number * 60,
+            's' => number,
+            _ => return None,
+        };
+    }
+    Some(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::Config;
+    use crate::test_server;
+
+    fn article(text: &str) -> String {
+        format!("<html><body><main><article><p>{}</p></article></main></body></html>", text)
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        assert_eq!(
+            parse_verdict(&article("That's the right answer! You are one gold star closer.")),
+            Verdict::Correct);
+        assert_eq!(
+            parse_verdict(&article("That's not the right answer; your answer is too high. Please wait one minute before trying again.")),
+            Verdict::TooHigh);
+        assert_eq!(
+            parse_verdict(&article("That's not the right answer; your answer is too low.")),
+            Verdict::TooLow);
+        assert_eq!(
+            parse_verdict(&article("That's not the right answer. If you're stuck, ...")),
+            Verdict::Incorrect);
+        assert_eq!(
+            parse_verdict(&article("You gave an answer too recently; you have to wait after submitting an answer before trying again. You have 1m 5s left to wait.")),
+            Verdict::RateLimited(Some(Duration::from_secs(65))));
+        assert_eq!(
+            parse_verdict(&article("You don't seem to be solving the right level. Did you already complete it?")),
+            Verdict::AlreadySolved);
+    }
+
+    #[test]
+    fn test_parse_wait_time() {
+        assert_eq!(parse_wait_time("You have 2h 1s left to wait."), Some(Duration::from_secs(7201)));
+        assert_eq!(parse_wait_time("You have 30é left to wait."), None);
+        assert_eq!(parse_wait_time("You have  left to wait."), None);
+    }
+
+    #[test]
+    fn test_submit_answer() {
+        let (base_url, server) = test_server::serve(vec![
+            (200, article("That's not the right answer; your answer is too low.")),
+        ]);
+        let http = Http::new(Config { base_url, ..Config::default() }).unwrap();
+
+        let verdict = submit_answer(&http, "s3cr3t", 2022, 5, 2, "MCD").unwrap();
+        assert_eq!(verdict, Verdict::TooLow);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].head[0], "POST /2022/day/5/answer HTTP/1.1");
+        assert_eq!(requests[0].header("cookie"), Some("session=s3cr3t"));
+        assert_eq!(requests[0].body, "level=2&answer=MCD");
+    }
+}
@@ -0,0 +1,61 @@
+//! A minimal stand-in for the Advent of Code server, for testing HTTP requests.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+pub(crate) struct Request {
+    /// Request line and headers, without line terminators. Header names are lowercased.
+    pub head: Vec<String>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head[1..]
+            .iter()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+    }
+}
+
+/// Starts a server that answers one request per given `(status, body)` pair, in order, then stops.
+/// Returns its base URL and a handle that returns the requests it received.
+pub(crate) fn serve(responses: Vec<(u16, String)>) -> (String, JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        responses
+            .into_iter()
+            .map(|(status, response)| {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut head = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    head.push(match line.split_once(": ") {
+                        Some((name, value)) if !head.is_empty() => {
+                            format!("{}: {}", name.to_lowercase(), value)
+                        },
+                        _ => line.to_owned(),
+                    });
+                }
+                let mut request = Request { head, body: String::new() };
+                let content_length = request.header("content-length").map_or(0, |len| len.parse().unwrap());
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                request.body = String::from_utf8(body).unwrap();
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {} Whatever\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, response.len(), response).unwrap();
+                request
+            })
+            .collect()
+    });
+    (base_url, handle)
+}
@@ -0,0 +1,5 @@
+fn part1(_input: &str) -> usize {
+    0
+}
+
+aoc::main!(part1 = part1, examples = [({{example}}, 0)], input = 0);
@@ -0,0 +1,39 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Generates `days.rs` for the `aoc` runner binary, which includes every `src/bin/NN.rs` file as a
+/// module and returns a list of their solutions.
+fn main() {
+    let bin_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src/bin");
+    println!("cargo:rerun-if-changed={}", bin_dir.display());
+
+    let mut days = fs::read_dir(&bin_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension().map_or(false, |ext| ext == "rs") &&
+                path.file_stem().unwrap().to_str().map_or(false, |stem| {
+                    stem.len() == 2 && stem.bytes().all(|c| c.is_ascii_digit())
+                })
+        })
+        .collect::<Vec<_>>();
+    days.sort();
+
+    let mut out = String::new();
+    for path in &days {
+        let stem = path.file_stem().unwrap().to_str().unwrap();
+        writeln!(out, "#[path = {:?}]\nmod day{};", path.to_str().unwrap(), stem).unwrap();
+    }
+    writeln!(out, "fn solutions() -> Vec<aoc::Solution> {{").unwrap();
+    writeln!(out, "    vec![").unwrap();
+    for path in &days {
+        let stem = path.file_stem().unwrap().to_str().unwrap();
+        writeln!(out, "        day{}::solution(),", stem).unwrap();
+    }
+    writeln!(out, "    ]").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    fs::write(Path::new(&env::var("OUT_DIR").unwrap()).join("days.rs"), out).unwrap();
+}
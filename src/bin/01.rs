@@ -17,14 +17,4 @@ fn run(input: &str) -> (u64, u64) {
     (*top3.last().unwrap(), top3.iter().sum())
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), (24000, 45000));
-}
-
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (66616, 199172));
-}
-
-aoc::main!(run);
+aoc::main!(run, examples = [(0, (24000, 45000))], input = (66616, 199172));
@@ -102,14 +102,4 @@ fn run(input: &str) -> (u64, u64) {
         .unwrap_or((0, 0))
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), (15, 12));
-}
-
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (15632, 14416));
-}
-
-aoc::main!(run);
+aoc::main!(run, examples = [(0, (15, 12))], input = (15632, 14416));
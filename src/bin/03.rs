@@ -57,14 +57,4 @@ fn run(input: &str) -> (u64, u64) {
     (part1, part2)
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), (157, 70));
-}
-
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (7850, 2581));
-}
-
-aoc::main!(run);
+aoc::main!(run, examples = [(0, (157, 70))], input = (7850, 2581));
@@ -1,49 +1,43 @@
 use std::cmp::{max, min};
 use std::ops::RangeInclusive;
 
-use itertools::Itertools;
-
-fn parse_range(s: &str) -> RangeInclusive<u64> {
-    let (start, end) = s
-        .split('-')
-        .map(|x| x.parse().unwrap())
-        .collect_tuple()
-        .unwrap();
-    start..=end
-}
+use aoc::{Parse, ParseError};
+use aoc2022::parse::{lines, parse_all, range};
+use nom::character::complete::char;
+use nom::sequence::separated_pair;
 
 fn contains(a: &RangeInclusive<u64>, b: &RangeInclusive<u64>) -> bool {
-    a.contains(&b.start()) && a.contains(&b.end())
+    a.contains(b.start()) && a.contains(b.end())
 }
 
 fn overlaps(a: &RangeInclusive<u64>, b: &RangeInclusive<u64>) -> bool {
     min(a.end(), b.end()) >= max(a.start(), b.start())
 }
 
-fn run(input: &str) -> (u64, u64) {
-    input
-        .lines()
-        .map(|line| {
-            let (a, b) = line
-                .split(',')
-                .map(parse_range)
-                .collect_tuple()
-                .unwrap();
-            let contains = (contains(&a, &b) || contains(&b, &a)) as u64;
-            let overlaps = overlaps(&a, &b) as u64;
-            (contains, overlaps)
-        })
-        .fold((0, 0), |(a1, o1), (a2, o2)| (a1 + a2, o1 + o2))
+type Pair = (RangeInclusive<u64>, RangeInclusive<u64>);
+
+struct Pairs(Vec<Pair>);
+
+impl Parse for Pairs {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        parse_all(input, lines(separated_pair(range::<_, nom::error::Error<_>>, char(','), range)))
+            .map(Pairs)
+    }
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), (2, 4));
+fn part1(pairs: &Pairs) -> usize {
+    pairs.0
+        .iter()
+        .filter(|(a, b)| contains(a, b) || contains(b, a))
+        .count()
 }
 
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (453, 919));
+fn part2(pairs: &Pairs) -> usize {
+    pairs.0
+        .iter()
+        .filter(|(a, b)| overlaps(a, b))
+        .count()
 }
 
-aoc::main!(run);
+aoc::main!(input_type = Pairs, part1 = part1, part2 = part2,
+    examples = [(0, (2, 4))], input = (453, 919));
@@ -1,76 +1,170 @@
-fn consume<'a>(iter: &mut impl Iterator<Item = &'a str>, expected: &str) {
-    let actual = iter.next();
-    debug_assert_eq!(actual, Some(expected));
+use std::str::Split;
+
+use aoc::{Parse, ParseError};
+
+struct Move {
+    count: usize,
+    from: usize,
+    to: usize,
 }
 
-fn run(input: &str) -> (String, String) {
-    let mut lines = input.lines();
-    let mut num_stacks = 0;
-    let mut stacks = Vec::new();
-    while let Some(line) = lines.next() {
-        let line = line.as_bytes();
-        num_stacks = num_stacks.max((line.len() + 1) / 4);
-        if stacks.len() < num_stacks {
-            stacks.resize(num_stacks, Vec::new());
-        }
-        if line[1] == b'1' {
-            break;
+struct Puzzle {
+    /// Crates in each stack, from bottom to top.
+    stacks: Vec<Vec<u8>>,
+    moves: Vec<Move>,
+}
+
+/// The words of a single line of the input, for parsing lines like `move 1 from 2 to 1`.
+struct Words<'a> {
+    input: &'a str,
+    line: &'a str,
+    words: Split<'a, char>,
+}
+
+impl<'a> Words<'a> {
+    fn new(input: &'a str, line: &'a str) -> Self {
+        Self { input, line, words: line.split(' ') }
+    }
+
+    fn next(&mut self, expected: &str) -> Result<&'a str, ParseError> {
+        let end_of_line = &self.line[self.line.len()..];
+        self.words.next().ok_or_else(|| {
+            ParseError::at(self.input, end_of_line, format!("expected {}, found end of line", expected))
+        })
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        let word = self.next(&format!("{:?}", keyword))?;
+        if word != keyword {
+            return Err(ParseError::expected(self.input, word, format!("{:?}", keyword)));
         }
-        for i in 0..num_stacks {
-            let crate_ = line[1 + 4*i];
-            if crate_ != b' ' {
-                stacks[i].push(crate_);
-            }
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<usize, ParseError> {
+        let word = self.next("number")?;
+        word.parse().map_err(|_| ParseError::expected(self.input, word, "number"))
+    }
+
+    /// Parses a 1-based stack number and returns its 0-based index.
+    fn stack(&mut self, num_stacks: usize) -> Result<usize, ParseError> {
+        let word = self.next("stack number")?;
+        match word.parse::<usize>() {
+            Ok(stack) if (1..=num_stacks).contains(&stack) => Ok(stack - 1),
+            _ => {
+                let expected = format!("stack number from 1 to {}", num_stacks);
+                Err(ParseError::expected(self.input, word, expected))
+            },
         }
     }
-    for stack in stacks.iter_mut() {
-        stack.reverse();
+
+    fn end(&mut self) -> Result<(), ParseError> {
+        match self.words.next() {
+            Some(word) => Err(ParseError::expected(self.input, word, "end of line")),
+            None => Ok(()),
+        }
     }
+}
 
-    let blank_line = lines.next().unwrap();
-    debug_assert_eq!(blank_line, "");
+impl Parse for Puzzle {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let end_of_input = &input[input.len()..];
+        let mut lines = input.lines();
 
-    let mut part1_stacks = stacks.clone();
-    let mut part2_stacks = stacks;
-    for line in lines {
-        let mut parts = line.split(' ');
-        consume(&mut parts, "move");
-        let count = parts.next().unwrap().parse::<usize>().unwrap();
-        consume(&mut parts, "from");
-        let from = parts.next().unwrap().parse::<usize>().unwrap() - 1;
-        consume(&mut parts, "to");
-        let to = parts.next().unwrap().parse::<usize>().unwrap() - 1;
+        let mut num_stacks = 0;
+        let mut stacks = Vec::new();
+        loop {
+            let line = lines.next()
+                .ok_or_else(|| ParseError::expected(input, end_of_input, "row of stack numbers"))?;
+            let bytes = line.as_bytes();
+            num_stacks = num_stacks.max((bytes.len() + 1) / 4);
+            if stacks.len() < num_stacks {
+                stacks.resize(num_stacks, Vec::new());
+            }
+            if bytes.get(1) == Some(&b'1') {
+                break;
+            }
+            for (i, stack) in stacks.iter_mut().enumerate() {
+                match bytes.get(1 + 4 * i) {
+                    Some(b' ') | None => {},
+                    Some(crate_) if crate_.is_ascii_uppercase() => stack.push(*crate_),
+                    Some(_) => {
+                        let location = line.get(1 + 4 * i..).unwrap_or(line);
+                        return Err(ParseError::expected(input, location, "crate letter or space"));
+                    },
+                }
+            }
+        }
+        for stack in stacks.iter_mut() {
+            stack.reverse();
+        }
 
-        for _ in 0..count {
-            let crate_ = part1_stacks[from].pop().unwrap();
-            part1_stacks[to].push(crate_);
+        match lines.next() {
+            Some("") => {},
+            Some(line) => return Err(ParseError::expected(input, line, "blank line")),
+            None => return Err(ParseError::expected(input, end_of_input, "blank line")),
         }
 
-        let idx = part2_stacks[from].len() - count;
-        let crates = part2_stacks[from][idx..].to_vec();
-        part2_stacks[to].extend(crates);
-        part2_stacks[from].truncate(idx);
+        let moves = lines
+            .map(|line| {
+                let mut words = Words::new(input, line);
+                words.keyword("move")?;
+                let count = words.number()?;
+                words.keyword("from")?;
+                let from = words.stack(num_stacks)?;
+                words.keyword("to")?;
+                let to = words.stack(num_stacks)?;
+                words.end()?;
+                Ok(Move { count, from, to })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { stacks, moves })
     }
+}
 
-    let part1 = part1_stacks
-        .into_iter()
-        .filter_map(|stack| stack.last().copied())
-        .collect::<Vec<u8>>();
-    let part2 = part2_stacks
-        .into_iter()
-        .filter_map(|stack| stack.last().copied())
-        .collect::<Vec<u8>>();
-    unsafe { (String::from_utf8_unchecked(part1), String::from_utf8_unchecked(part2)) }
+fn top_crates(stacks: &[Vec<u8>]) -> String {
+    stacks
+        .iter()
+        .filter_map(|stack| stack.last())
+        .map(|&crate_| crate_ as char)
+        .collect()
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), ("CMZ".to_owned(), "MCD".to_owned()));
+fn part1(puzzle: &Puzzle) -> String {
+    let mut stacks = puzzle.stacks.clone();
+    for &Move { count, from, to } in &puzzle.moves {
+        for _ in 0..count {
+            let crate_ = stacks[from].pop().unwrap();
+            stacks[to].push(crate_);
+        }
+    }
+    top_crates(&stacks)
+}
+
+fn part2(puzzle: &Puzzle) -> String {
+    let mut stacks = puzzle.stacks.clone();
+    for &Move { count, from, to } in &puzzle.moves {
+        let idx = stacks[from].len() - count;
+        let crates = stacks[from].split_off(idx);
+        stacks[to].extend(crates);
+    }
+    top_crates(&stacks)
 }
 
 #[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), ("VPCDMSLWJ".to_owned(), "TPWCGNCCG".to_owned()));
+fn parse_errors() {
+    let message = |input: &str| Puzzle::parse(input).err().unwrap().message;
+    assert!(Puzzle::parse("[A]\n 1 \n\nmove 1 from 1 to 1\n").is_ok());
+    assert_eq!(message("[A]\n 1 \n\nmove 1 from 1 too 1\n"), "expected \"to\", found \"too\"");
+    assert_eq!(
+        message("[A]\n 1 \n\nmove 1 from 2 to 1\n"),
+        "expected stack number from 1 to 1, found \"2\"");
+    assert_eq!(message("[A]\n 1 \n\nmove 1 from 1\n"), "expected \"to\", found end of line");
+    assert_eq!(message("[a]\n 1 \n"), "expected crate letter or space, found \"a]\"");
+    assert_eq!(message("[A]\n 1 \nmove"), "expected blank line, found \"move\"");
 }
 
-aoc::main!(run);
+aoc::main!(input_type = Puzzle, part1 = part1, part2 = part2,
+    examples = [(0, ("CMZ".to_owned(), "MCD".to_owned()))],
+    input = ("VPCDMSLWJ".to_owned(), "TPWCGNCCG".to_owned()));
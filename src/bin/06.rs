@@ -51,17 +51,11 @@ fn run(input: &str) -> (usize, usize) {
 }
 
 #[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), (7, 19));
+fn inline_examples() {
     assert_eq!(run("bvwbjplbgvbhsrlpgdmjqwftvncz"), (5, 23));
     assert_eq!(run("nppdvjthqldpwncqszvftbrmjlhg"), (6, 23));
     assert_eq!(run("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), (10, 29));
     assert_eq!(run("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), (11, 26));
 }
 
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (1042, 2980));
-}
-
-aoc::main!(run);
+aoc::main!(run, examples = [(0, (7, 19))], input = (1042, 2980));
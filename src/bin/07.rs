@@ -1,62 +1,87 @@
 use std::path::{Path, PathBuf};
 use std::collections::BTreeMap;
 
-fn run(input: &str) -> (u64, u64) {
-    let mut file_sizes = BTreeMap::new();
-    let mut pwd = PathBuf::from("/");
-    for line in input.lines() {
-        let mut parts = line.split(" ");
-        match (parts.next().unwrap(), parts.next().unwrap()) {
-            ("$", "cd") => {
-                match parts.next().unwrap() {
-                    ".." => { pwd.pop(); },
-                    "/" => { pwd = PathBuf::from("/"); },
-                    subdir => { pwd.push(subdir); },
-                }
-                debug_assert!(parts.next().is_none());
-            },
-            ("$", "ls") => {
-            },
-            ("dir", _) => {
-            },
-            (size, file_name) => {
-                file_sizes.insert(pwd.join(file_name), size.parse::<u64>().unwrap());
-            },
+use aoc::{Parse, ParseError};
+
+/// The sizes of all files listed in the terminal output, by absolute path.
+#[derive(Debug, PartialEq)]
+struct Filesystem {
+    file_sizes: BTreeMap<PathBuf, u64>,
+}
+
+impl Parse for Filesystem {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut file_sizes = BTreeMap::new();
+        let mut pwd = PathBuf::from("/");
+        for line in input.lines() {
+            let parts = line.split(' ').collect::<Vec<_>>();
+            match parts[..] {
+                ["$", "cd", ".."] => { pwd.pop(); },
+                ["$", "cd", "/"] => { pwd = PathBuf::from("/"); },
+                ["$", "cd", subdir] => { pwd.push(subdir); },
+                ["$", "ls"] => {},
+                ["$", ..] => {
+                    return Err(ParseError::at(input, line, "expected `$ cd DIR` or `$ ls`"));
+                },
+                ["dir", _] => {},
+                [size, file_name] => {
+                    let size = size.parse::<u64>()
+                        .map_err(|_| ParseError::expected(input, size, "file size or `dir`"))?;
+                    file_sizes.insert(pwd.join(file_name), size);
+                },
+                _ => {
+                    return Err(ParseError::at(input, line, "expected command or directory listing"));
+                },
+            }
         }
-        debug_assert!(parts.next().is_none());
+        Ok(Self { file_sizes })
     }
+}
 
-    let mut dir_sizes = BTreeMap::new();
-    for (file_name, size) in file_sizes {
-        for ancestor in file_name.parent().unwrap().ancestors() {
-            *dir_sizes.entry(ancestor.to_owned()).or_default() += size;
+impl Filesystem {
+    fn dir_sizes(&self) -> BTreeMap<PathBuf, u64> {
+        let mut dir_sizes = BTreeMap::new();
+        for (file_name, size) in &self.file_sizes {
+            for ancestor in file_name.parent().unwrap().ancestors() {
+                *dir_sizes.entry(ancestor.to_owned()).or_default() += size;
+            }
         }
+        dir_sizes
     }
+}
 
-    let part1 = dir_sizes
+fn part1(fs: &Filesystem) -> u64 {
+    fs.dir_sizes()
         .values()
         .filter(|&&size| size <= 100000)
-        .sum();
+        .sum()
+}
 
+fn part2(fs: &Filesystem) -> u64 {
+    let dir_sizes = fs.dir_sizes();
     let free_space = 70000000 - dir_sizes[Path::new("/")];
     let min_size = 30000000 - free_space;
-    let part2 = *dir_sizes
+    *dir_sizes
         .values()
         .filter(|&&size| size >= min_size)
         .min()
-        .unwrap();
-
-    (part1, part2)
-}
-
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(1)), (95437, 24933642));
+        .unwrap()
 }
 
 #[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (1723892, 8474158));
+fn parse() {
+    let fs = Filesystem::parse("$ cd /\n$ ls\ndir a\n14848514 b.txt\n$ cd a\n$ ls\n584 i\n").unwrap();
+    assert_eq!(fs.file_sizes, BTreeMap::from([
+        (PathBuf::from("/b.txt"), 14848514),
+        (PathBuf::from("/a/i"), 584),
+    ]));
+    let err = Filesystem::parse("$ cd /\n$ ls\n12k b.txt\n").unwrap_err();
+    assert_eq!((err.line, err.column), (3, 1));
+    assert_eq!(err.message, "expected file size or `dir`, found \"12k\"");
+    let err = Filesystem::parse("$ cd /\n$ rm -rf\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 1));
+    assert_eq!(err.message, "expected `$ cd DIR` or `$ ls`");
 }
 
-aoc::main!(run);
+aoc::main!(input_type = Filesystem, part1 = part1, part2 = part2,
+    examples = [(1, (95437, 24933642))], input = (1723892, 8474158));
@@ -53,14 +53,8 @@ fn run(input: &str) -> (usize, usize) {
 }
 
 #[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(3)), (13, 1));
+fn larger_example() {
     assert_eq!(run(&aoc::example!(7)).1, 36);
 }
 
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (6269, 2557));
-}
-
-aoc::main!(run);
+aoc::main!(run, examples = [(3, (13, 1))], input = (6269, 2557));
@@ -1,31 +1,33 @@
 use aoc2022::crt::*;
 
-fn run(input: &str) -> (i64, String) {
-    let mut instrs = input
-        .lines()
-        .map(|line| line.parse::<Instruction>().unwrap());
-    let mut crt = Crt::new();
-    let mut signal_strength = 0;
-    loop {
-        let x = crt.x();
-        if !crt.tick(&mut instrs) {
-            break;
-        }
-        if (crt.cycle() - 20) % 40 == 0 {
-            signal_strength += crt.cycle() * x;
-        }
-    }
-    (signal_strength, crt.screen())
+fn run_crt(program: &Program) -> (i64, Crt) {
+    let mut crt = Crt::new(program.instructions.clone());
+    let signal_strength = crt
+        .cycles()
+        .filter(|state| state.cycle % 40 == 20)
+        .map(|state| state.cycle * state.x_during)
+        .sum();
+    (signal_strength, crt)
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(1)), (13140, aoc::example!(4)));
+fn part1(program: &Program) -> i64 {
+    run_crt(program).0
+}
+
+fn part2(program: &Program) -> String {
+    let (_, crt) = run_crt(program);
+    crt.letters().unwrap_or_else(|err| panic!("{}\n{}", err, crt.screen()))
 }
 
+/// The example doesn't draw letters, so it's checked against the screen instead.
 #[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (17840, "####..##..#.....##..#..#.#....###...##..\n#....#..#.#....#..#.#..#.#....#..#.#..#.\n###..#..#.#....#....#..#.#....#..#.#....\n#....####.#....#.##.#..#.#....###..#.##.\n#....#..#.#....#..#.#..#.#....#....#..#.\n####.#..#.####..###..##..####.#.....###.\n".to_owned()));
+fn example() {
+    use aoc::Parse;
+
+    let program = Program::parse(&aoc::example!(1)).unwrap();
+    let (signal_strength, crt) = run_crt(&program);
+    assert_eq!(signal_strength, 13140);
+    assert_eq!(crt.screen(), aoc::example!(4));
 }
 
-aoc::main!(run);
+aoc::main!(input_type = Program, part1 = part1, part2 = part2, input = (17840, "EALGULPG".to_owned()));
@@ -1,22 +1,14 @@
+use aoc::{Parse, ParseError};
+use aoc2022::parse::{blocks, comma_list, key_value, line, parse_all, unsigned};
 use nom::IResult;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::combinator::{all_consuming, map};
-use nom::character::complete::{digit1, multispace0};
-use nom::error::Error;
-use nom::multi::{many0, separated_list0};
+use nom::combinator::{consumed, map};
+use nom::character::complete::{char, space0};
 use nom::sequence::{delimited, preceded, tuple};
 
 type Item = u64;
 
-fn index(input: &str) -> IResult<&str, usize> {
-    map(digit1, |s: &str| s.parse::<usize>().unwrap())(input)
-}
-
-fn item(input: &str) -> IResult<&str, Item> {
-    map(digit1, |s: &str| s.parse::<Item>().unwrap())(input)
-}
-
 #[derive(Clone, Debug)]
 enum Operator {
     Add,
@@ -42,7 +34,7 @@ impl Operand {
     fn parse(input: &str) -> IResult<&str, Self> {
         alt((
             map(tag("old"), |_| Operand::Old),
-            map(item, |v| Operand::Value(v)),
+            map(unsigned, Operand::Value),
         ))(input)
     }
 
@@ -64,7 +56,7 @@ struct Operation {
 impl Operation {
     fn parse(input: &str) -> IResult<&str, Self> {
         map(
-            tuple((Operand::parse, multispace0, Operator::parse, multispace0, Operand::parse)),
+            tuple((Operand::parse, space0, Operator::parse, space0, Operand::parse)),
             |(lhs, _, operator, _, rhs)| Self { lhs, operator, rhs })
             (input)
     }
@@ -87,7 +79,7 @@ struct Test {
 impl Test {
     fn parse(input: &str) -> IResult<&str, Self> {
         map(
-            preceded(tuple((tag("divisible by"), multispace0)), item),
+            preceded(tag("divisible by "), unsigned),
             |divisible_by| Test { divisible_by })
             (input)
     }
@@ -105,7 +97,7 @@ struct Action {
 impl Action {
     fn parse(input: &str) -> IResult<&str, Self> {
         map(
-            preceded(tuple((tag("throw to monkey"), multispace0)), index),
+            preceded(tag("throw to monkey "), unsigned),
             |throw_to_monkey| Action { throw_to_monkey })
             (input)
     }
@@ -128,44 +120,37 @@ struct Monkey {
 
 impl Monkey {
     fn parse(input: &str) -> IResult<&str, Self> {
-        let s0 = multispace0;
         map(
             tuple((
-                delimited(
-                    tuple((s0, tag("Monkey"), s0)),
-                    index,
-                    tuple((s0, tag(":"), s0))),
-                delimited(
-                    tuple((tag("Starting items:"), s0)),
-                    separated_list0(tuple((s0, tag(","), s0)), item),
-                    s0),
-                delimited(
-                    tuple((tag("Operation:"), s0, tag("new"), s0, tag("="), s0)),
-                    Operation::parse,
-                    s0),
-                delimited(
-                    tuple((s0, tag("Test:"), s0)),
-                    Test::parse,
-                    s0),
-                delimited(
-                    tuple((s0, tag("If true:"), s0)),
-                    Action::parse,
-                    s0),
-                delimited(
-                    tuple((s0, tag("If false:"), s0)),
-                    Action::parse,
-                    s0),
+                line(delimited(tag("Monkey "), unsigned, char(':'))),
+                key_value("Starting items", comma_list(unsigned)),
+                key_value("Operation", preceded(tag("new = "), Operation::parse)),
+                key_value("Test", Test::parse),
+                key_value("If true", Action::parse),
+                key_value("If false", Action::parse),
             )),
             |(idx, items, operation, test, true_action, false_action)| {
-                assert!(true_action.throw_to_monkey != idx);
-                assert!(false_action.throw_to_monkey != idx);
                 Self { idx, items, operation, test, true_action, false_action, activity: 0 }
             })
             (input)
     }
 
-    fn parse_all(input: &str) -> Result<Vec<Self>, nom::Err<Error<&str>>> {
-        Ok(all_consuming(many0(Self::parse))(input)?.1)
+    /// Checks that this monkey, parsed from `text`, is the `idx`th of `len` monkeys, and only throws
+    /// to other monkeys that exist. Otherwise, returns the offending line and a message.
+    fn check<'a>(&self, text: &'a str, idx: usize, len: usize) -> Result<(), (&'a str, String)> {
+        if self.idx != idx {
+            return Err((text, format!("expected monkey {}, found monkey {}", idx, self.idx)));
+        }
+        for (key, action) in [("If true", &self.true_action), ("If false", &self.false_action)] {
+            let line = text.lines().map(str::trim_start).find(|line| line.starts_with(key)).unwrap();
+            if action.throw_to_monkey == idx {
+                return Err((line, format!("monkey {} throws to itself", idx)));
+            }
+            if action.throw_to_monkey >= len {
+                return Err((line, format!("monkey {} does not exist", action.throw_to_monkey)));
+            }
+        }
+        Ok(())
     }
 
     fn inspect(&mut self, item: Item) -> Item {
@@ -186,7 +171,7 @@ fn monkey_business(mut monkeys: Vec<Monkey>, relief: impl Fn(Item) -> Item, roun
     let product = monkeys
         .iter()
         .map(|monkey| monkey.test.divisible_by)
-        .fold(1, |a, b| a * b);
+        .product::<Item>();
     for _round in 0..rounds {
         for i in 0..monkeys.len() {
             let items = std::mem::take(&mut monkeys[i].items);
@@ -201,23 +186,45 @@ fn monkey_business(mut monkeys: Vec<Monkey>, relief: impl Fn(Item) -> Item, roun
     monkeys.iter().rev().take(2).map(|monkey| monkey.activity).reduce(|a, b| a * b).unwrap()
 }
 
-fn run(input: &str) -> (usize, usize) {
-    let monkeys = Monkey::parse_all(input).unwrap();
-    assert!(monkeys.iter().enumerate().all(|(i, monkey)| monkey.idx == i));
-    (
-        monkey_business(monkeys.clone(), |item| item / 3, 20),
-        monkey_business(monkeys, |item| item, 10000),
-    )
+#[derive(Debug)]
+struct Monkeys(Vec<Monkey>);
+
+impl Parse for Monkeys {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let monkeys = parse_all(input, blocks(consumed(Monkey::parse)))?;
+        let len = monkeys.len();
+        for (idx, (text, monkey)) in monkeys.iter().enumerate() {
+            monkey.check(text, idx, len).map_err(|(line, message)| ParseError::at(input, line, message))?;
+        }
+        Ok(Self(monkeys.into_iter().map(|(_, monkey)| monkey).collect()))
+    }
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), (10605, 2713310158));
+fn part1(monkeys: &Monkeys) -> usize {
+    monkey_business(monkeys.0.clone(), |item| item / 3, 20)
 }
 
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (117624, 16792940265));
+fn part2(monkeys: &Monkeys) -> usize {
+    monkey_business(monkeys.0.clone(), |item| item, 10000)
 }
 
-aoc::main!(run);
+#[test]
+fn invalid_monkeys() {
+    let monkey = |idx, if_true, if_false| format!(
+        "Monkey {}:\n  Starting items: 79, 98\n  Operation: new = old * 19\n  Test: divisible by 23\n    \
+        If true: throw to monkey {}\n    If false: throw to monkey {}\n", idx, if_true, if_false);
+    assert!(Monkeys::parse(&format!("{}\n{}", monkey(0, 1, 1), monkey(1, 0, 0))).is_ok());
+
+    let err = Monkeys::parse(&format!("{}\n{}", monkey(0, 1, 1), monkey(2, 0, 0))).unwrap_err();
+    assert_eq!((err.line, err.column), (8, 1));
+    assert_eq!(err.message, "expected monkey 1, found monkey 2");
+    let err = Monkeys::parse(&format!("{}\n{}", monkey(0, 1, 1), monkey(1, 0, 1))).unwrap_err();
+    assert_eq!((err.line, err.column), (13, 5));
+    assert_eq!(err.message, "monkey 1 throws to itself");
+    let err = Monkeys::parse(&format!("{}\n{}", monkey(0, 2, 1), monkey(1, 0, 0))).unwrap_err();
+    assert_eq!((err.line, err.column), (5, 5));
+    assert_eq!(err.message, "monkey 2 does not exist");
+}
+
+aoc::main!(input_type = Monkeys, part1 = part1, part2 = part2,
+    examples = [(0, (10605, 2713310158))], input = (117624, 16792940265));
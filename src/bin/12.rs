@@ -1,59 +1,112 @@
-use std::collections::VecDeque;
+use std::fmt;
+
+use aoc::{Parse, ParseError};
+use aoc2022::grid::{Grid, DOWN, LEFT, RIGHT, UP};
+use aoc2022::search::bfs;
+use aoc2022::trace;
+use glam::IVec2;
 
-const VISITED: u8 = b'#';
 const MIN: u8 = b'a';
 const MAX: u8 = b'z';
 const START: u8 = b'S';
 const END: u8 = b'E';
 
-fn run(input: &str) -> (usize, usize) {
-    let mut grid = input.as_bytes().to_vec();
-    let col_stride = grid.iter().position(|&h| h == b'\n').unwrap() + 1;
-    let start = grid.iter().position(|&h| h == START).unwrap();
-    let end = grid.iter().position(|&h| h == END).unwrap();
-    grid[start] = MIN;
-    grid[end] = MAX;
-
-    let mut part2 = usize::MAX;
-    let mut queue = VecDeque::new();
-    queue.push_back((end, 0));
-    while let Some((curr, steps)) = queue.pop_front() {
-        let height = grid[curr];
-        if height == VISITED {
-            continue;
-        }
-        grid[curr] = VISITED;
+struct Heightmap {
+    heights: Grid<u8>,
+    start: IVec2,
+    end: IVec2,
+}
 
-        if height == MIN {
-            part2 = part2.min(steps);
-        }
-        if curr == start {
-            debug_assert!(part2 <= steps);
-            return (steps, part2);
+impl Parse for Heightmap {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut heights = Grid::parse_with(input, |c| match c {
+            'a'..='z' | 'S' | 'E' => Some(c as u8),
+            _ => None,
+        })?;
+        let end_of_input = &input[input.len()..];
+        let start = heights.position(|&h| h == START)
+            .ok_or_else(|| ParseError::at(input, end_of_input, "no start position 'S' found"))?;
+        let end = heights.position(|&h| h == END)
+            .ok_or_else(|| ParseError::at(input, end_of_input, "no end position 'E' found"))?;
+        heights[start] = MIN;
+        heights[end] = MAX;
+        Ok(Self { heights, start, end })
+    }
+}
+
+impl Heightmap {
+    /// Finds a shortest route from any of `sources` to the end, where each step may climb at most
+    /// one unit.
+    fn climb(&self, sources: impl IntoIterator<Item = IVec2>) -> Option<Route<'_>> {
+        let heights = &self.heights;
+        let search = bfs(
+            sources,
+            |&pos| heights.neighbors4(pos).filter(move |&next| heights[next] <= heights[pos] + 1),
+            |&pos| pos == self.end);
+        Some(Route { map: self, path: search.goal_path()? })
+    }
+
+    fn lowest(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.heights.iter().filter(|(_, &h)| h == MIN).map(|(pos, _)| pos)
+    }
+}
+
+/// A route over the heightmap, including its first and last positions. Displayed like in the
+/// puzzle text, with an arrow on each position pointing to the next one.
+struct Route<'a> {
+    map: &'a Heightmap,
+    path: Vec<IVec2>,
+}
+
+impl Route<'_> {
+    fn steps(&self) -> usize {
+        self.path.len() - 1
+    }
+
+    /// Prints the route to stderr if the `AOC_TRACE` environment variable is set, the first time
+    /// this is called for `key`.
+    fn trace(self, key: &'static str) -> Self {
+        if trace::every(key).is_some() {
+            eprintln!("{}", self);
         }
+        self
+    }
+}
 
-        for step in [1, 0_usize.wrapping_sub(1), col_stride, 0_usize.wrapping_sub(col_stride)] {
-            let neigh = curr.wrapping_add(step);
-            if let Some(neigh_height) = grid.get(neigh).copied() {
-                if (MIN..=MAX).contains(&neigh_height) { // Checks for b'\n' and VISITED
-                    if height <= neigh_height + 1 {
-                        queue.push_back((neigh, steps + 1));
-                    }
-                }
-            }
+impl fmt::Display for Route<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut arrows = self.map.heights.map(|_| '.');
+        for step in self.path.windows(2) {
+            arrows[step[0]] = match step[1] - step[0] {
+                RIGHT => '>',
+                DOWN => 'v',
+                LEFT => '<',
+                UP => '^',
+                _ => unreachable!(),
+            };
         }
+        arrows[self.map.end] = 'E';
+        f.write_str(&arrows.render(|_, &arrow| arrow))
     }
-    panic!();
 }
 
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), (31, 29));
+fn part1(map: &Heightmap) -> usize {
+    map.climb([map.start]).unwrap().trace("part 1").steps()
+}
+
+fn part2(map: &Heightmap) -> usize {
+    map.climb(map.lowest()).unwrap().trace("part 2").steps()
 }
 
 #[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), (528, 522));
+fn route() {
+    let map = Heightmap::parse("Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n").unwrap();
+    let route = map.climb([map.start]).unwrap();
+    assert_eq!(route.path.first(), Some(&map.start));
+    assert_eq!(route.steps(), 31);
+    assert_eq!(route.to_string(), ">>vv<<<<\n..vvv<<^\n..vv>E^^\n..v>>>^^\n..>>>>>^\n");
+    assert_eq!(map.climb(map.lowest()).unwrap().steps(), 29);
 }
 
-aoc::main!(run);
+aoc::main!(input_type = Heightmap, part1 = part1, part2 = part2,
+    examples = [(0, (31, 29))], input = (528, 522));
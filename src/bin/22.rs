@@ -1,118 +1,322 @@
-struct Grid<'a> {
-    num_rows: usize,
-    num_cols: usize,
-    rows: Vec<&'a [u8]>,
+use aoc::{Parse, ParseError};
+use aoc2022::grid::{Grid, DIRECTIONS4};
+use aoc2022::parse::{parse_all, unsigned};
+use aoc2022::trace;
+use glam::{IVec2, IVec3};
+use nom::branch::alt;
+use nom::character::complete::char;
+use nom::combinator::{map, value};
+use nom::multi::many1;
+
+const OPEN: u8 = b'.';
+const WALL: u8 = b'#';
+const VOID: u8 = b' ';
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Instruction {
+    Forward(usize),
+    Left,
+    Right,
+}
+
+struct Notes {
+    map: Grid<u8>,
+    path: Vec<Instruction>,
+}
+
+impl Parse for Notes {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let (map_text, path) = input.split_once("\n\n")
+            .ok_or_else(|| ParseError::at(input, &input[input.len()..], "expected blank line before path"))?;
+        let tile = |c: char| [OPEN, WALL, VOID].contains(&(c as u8)).then_some(c as u8);
+        let tiles = Grid::parse_with(map_text, tile)?;
+        let path = path.trim_end();
+        let path = parse_all(path, many1(alt((
+            map(unsigned::<_, nom::error::Error<_>>, Instruction::Forward),
+            value(Instruction::Left, char('L')),
+            value(Instruction::Right, char('R')),
+        )))).map_err(|err| err.relocate(input, path))?;
+        Ok(Self { map: tiles, path })
+    }
 }
 
-impl<'a> Grid<'a> {
-    fn new(rows: Vec<&'a [u8]>) -> Self {
-        let num_rows = rows.len();
-        let num_cols = rows.iter().map(|row| row.len()).max().unwrap();
-        Self {
-            num_rows,
-            num_cols,
-            rows,
+/// Position on the map, and facing as an index into `DIRECTIONS4`.
+type State = (IVec2, usize);
+
+impl Notes {
+    fn is_void(&self, pos: IVec2) -> bool {
+        matches!(self.map.get(pos), None | Some(&VOID))
+    }
+
+    /// Steps off the edge of the map onto the tile at the other end of the row or column.
+    fn wrap_flat(&self, (pos, facing): State) -> State {
+        let step = DIRECTIONS4[facing];
+        // The map isn't empty, because we're standing on it.
+        let wrap = |pos| self.map.wrap(pos).unwrap();
+        let mut next = wrap(pos + step);
+        while self.is_void(next) {
+            next = wrap(next + step);
+        }
+        (next, facing)
+    }
+
+    /// Follows the path from the starting position, calling `wrap` to find where a step off the
+    /// edge of the map ends up. Calls `visit` with the starting state, and with the state after
+    /// every step and turn, along with the number of instructions started so far. Returns the
+    /// final state.
+    fn walk(&self, wrap: impl Fn(State) -> State, mut visit: impl FnMut(usize, State)) -> State {
+        let mut pos = self.map.position(|&c| c == OPEN).unwrap();
+        let mut facing = 0;
+        visit(0, (pos, facing));
+        for (i, &instruction) in self.path.iter().enumerate() {
+            match instruction {
+                Instruction::Forward(steps) => {
+                    for _ in 0..steps {
+                        let mut next = (pos + DIRECTIONS4[facing], facing);
+                        if self.is_void(next.0) {
+                            next = wrap((pos, facing));
+                        }
+                        if self.map[next.0] == WALL {
+                            break;
+                        }
+                        (pos, facing) = next;
+                        visit(i + 1, (pos, facing));
+                    }
+                },
+                Instruction::Left => {
+                    facing = (facing + 3) % 4;
+                    visit(i + 1, (pos, facing));
+                },
+                Instruction::Right => {
+                    facing = (facing + 1) % 4;
+                    visit(i + 1, (pos, facing));
+                },
+            }
         }
+        (pos, facing)
     }
 
-    fn get(&self, coord: Coord) -> u8 {
-        self.rows[coord.row as usize]
-            .get(coord.col as usize)
-            .copied()
-            .unwrap_or(b' ')
+    fn trace(&self, wrap: impl Fn(State) -> State) -> Trace {
+        let mut states = Vec::new();
+        let end = self.walk(wrap, |instructions, state| states.push((instructions, state)));
+        Trace { instructions: self.path.len(), states, end }
     }
 
-    fn wrap(&self, coord: Coord) -> Coord {
-        Coord {
-            row: coord.row.rem_euclid(self.num_rows as i64),
-            col: coord.col.rem_euclid(self.num_cols as i64),
+    /// Like `walk`, but if the `AOC_TRACE` environment variable is set to a number N, prints the
+    /// map with the walk drawn on it to stderr after every N instructions. Only the first walk for
+    /// each `key` is traced.
+    fn walk_traced(&self, key: &'static str, wrap: impl Fn(State) -> State) -> State {
+        match trace::every(key) {
+            Some(every) => {
+                let trace = self.trace(wrap);
+                for frame in trace.frames(&self.map, every) {
+                    eprintln!("{}", frame);
+                }
+                trace.end
+            },
+            None => self.walk(wrap, |_, _| {}),
         }
     }
 }
 
+/// Every state visited during a walk, with the number of instructions started when it was reached.
+struct Trace {
+    instructions: usize,
+    states: Vec<(usize, State)>,
+    end: State,
+}
+
+impl Trace {
+    /// Renders the map with the walk over the first `instructions` instructions drawn on it, like
+    /// in the puzzle text: each visited tile shows the last facing there as one of `>v<^`.
+    fn render(&self, map: &Grid<u8>, instructions: usize) -> String {
+        let mut marks = map.map(|&c| c as char);
+        for &(_, (pos, facing)) in self.states.iter().take_while(|(i, _)| *i <= instructions) {
+            marks[pos] = ['>', 'v', '<', '^'][facing];
+        }
+        marks.render(|_, &c| c)
+    }
+
+    /// Renders the walk after every `every` instructions, and at the end. `every` must be positive.
+    fn frames<'a>(&'a self, map: &'a Grid<u8>, every: usize) -> impl Iterator<Item = String> + 'a {
+        (every..self.instructions)
+            .step_by(every)
+            .chain([self.instructions])
+            .map(move |i| self.render(map, i))
+    }
+}
+
+fn password((pos, facing): State) -> usize {
+    1000 * (pos.y + 1) as usize + 4 * (pos.x + 1) as usize + facing
+}
+
+/// The map folded into a cube. Each face is a square of the map, which is given an orientation in
+/// 3D space by folding the net around the first face.
+struct Cube {
+    size: i32,
+    faces: Vec<Face>,
+}
+
+/// A face of the cube, with the 3D directions corresponding to the map's right and down
+/// directions, and its outward normal.
 #[derive(Clone, Copy, Debug)]
-struct Coord {
-    row: i64,
-    col: i64,
+struct Face {
+    origin: IVec2,
+    right: IVec3,
+    down: IVec3,
+    normal: IVec3,
 }
 
-impl std::ops::Add for Coord {
-    type Output = Coord;
-    fn add(self, rhs: Coord) -> Coord {
-        Coord {
-            row: self.row + rhs.row,
-            col: self.col + rhs.col,
+impl Face {
+    /// Converts a direction on the map into a 3D direction on this face.
+    fn direction_3d(&self, direction: IVec2) -> IVec3 {
+        self.right * direction.x + self.down * direction.y
+    }
+
+    /// The face that is reached by folding over the edge in the given direction on the map.
+    fn fold(&self, direction: IVec2, size: i32) -> Face {
+        let forward = self.direction_3d(direction);
+        // Rotating about the edge turns the direction of travel into the inward direction.
+        let folded = |v: IVec3| match v {
+            v if v == forward => -self.normal,
+            v if v == -forward => self.normal,
+            v => v,
+        };
+        Face {
+            origin: self.origin + direction * size,
+            right: folded(self.right),
+            down: folded(self.down),
+            normal: forward,
         }
     }
+
+    /// The 3D coordinates of the center of a cell of this face, in a cube that spans from `-size`
+    /// to `size` along each axis, so that cell centers have integer coordinates.
+    fn point_3d(&self, pos: IVec2, size: i32) -> IVec3 {
+        let local = (pos - self.origin) * 2 + 1 - size;
+        self.normal * size + self.right * local.x + self.down * local.y
+    }
+
+    /// The inverse of `point_3d`.
+    fn pos_2d(&self, point: IVec3, size: i32) -> IVec2 {
+        let local = IVec2::new(point.dot(self.right), point.dot(self.down));
+        self.origin + (local + size - 1) / 2
+    }
 }
 
-fn run(input: &str) -> usize {
-    let mut lines = input
-        .as_bytes()
-        .split(|&c| c == b'\n')
-        .collect::<Vec<&[u8]>>();
-    let instructions = loop {
-        let line = lines.pop().unwrap();
-        if !line.is_empty() {
-            break line;
+impl Cube {
+    fn fold(map: &Grid<u8>) -> Result<Self, String> {
+        let area = map.iter().filter(|(_, &c)| c != VOID).count();
+        let size = (1..).find(|size| 6 * size * size >= area).unwrap();
+        if 6 * size * size != area {
+            return Err(format!("map has {} tiles, which is not six square faces", area));
         }
-    };
-    let grid = Grid::new(lines);
-    let mut pos = Coord {
-        row: 0,
-        col: grid.rows[0].into_iter().position(|&c| c != b' ').unwrap() as i64,
-    };
-    let mut facing = 0;
-
-    let mut idx = 0;
-    while idx < instructions.len() {
-        let c = instructions[idx];
-        if c.is_ascii_digit() {
-            let start = idx;
-            while idx < instructions.len() && instructions[idx].is_ascii_digit() {
-                idx += 1;
-            }
-            let steps = std::str::from_utf8(&instructions[start..idx]).unwrap().parse::<usize>().unwrap();
-            for _ in 0..steps {
-                let step = match facing {
-                    0 => Coord { row: 0, col: 1 },
-                    1 => Coord { row: 1, col: 0 },
-                    2 => Coord { row: 0, col: -1 },
-                    3 => Coord { row: -1, col: 0 },
-                    _ => unreachable!(),
-                };
-                let mut next = grid.wrap(pos + step);
-                while grid.get(next) == b' ' {
-                    next = grid.wrap(next + step);
-                }
-                if grid.get(next) == b'#' {
-                    break;
+        let size = size as i32;
+
+        let is_face = |origin: IVec2| matches!(map.get(origin), Some(&c) if c != VOID);
+        let first = map.position(|&c| c != VOID).unwrap();
+        let mut faces = vec![Face { origin: first, right: IVec3::X, down: IVec3::Y, normal: IVec3::Z }];
+        let mut i = 0;
+        while i < faces.len() {
+            let face = faces[i];
+            for direction in DIRECTIONS4 {
+                let neighbor = face.fold(direction, size);
+                if is_face(neighbor.origin) && !faces.iter().any(|face| face.origin == neighbor.origin) {
+                    faces.push(neighbor);
                 }
-                assert!(grid.get(next) == b'.');
-                pos = next;
             }
-        } else if c == b'L' {
-            facing = (facing + 3) % 4;
-            idx += 1;
-        } else if c == b'R' {
-            facing = (facing + 1) % 4;
-            idx += 1;
-        } else if !c.is_ascii_whitespace() {
-            panic!();
+            i += 1;
         }
+        if faces.len() != 6 {
+            return Err(format!("map folds into {} connected faces instead of 6", faces.len()));
+        }
+        if faces.iter().any(|face| faces.iter().filter(|other| other.normal == face.normal).count() > 1) {
+            return Err("faces overlap when folded".to_owned());
+        }
+        Ok(Self { size, faces })
     }
-    
-    1000 * (pos.row + 1) as usize + 4 * (pos.col + 1) as usize + facing
+
+    fn face_at(&self, pos: IVec2) -> &Face {
+        let origin = pos / self.size * self.size;
+        self.faces.iter().find(|face| face.origin == origin).unwrap()
+    }
+
+    /// Steps off the edge of a face onto the adjacent face of the cube.
+    fn wrap(&self, (pos, facing): State) -> State {
+        let face = self.face_at(pos);
+        let forward = face.direction_3d(DIRECTIONS4[facing]);
+        let next_face = self.faces.iter().find(|face| face.normal == forward).unwrap();
+        let point = face.point_3d(pos, self.size) + forward - face.normal;
+        let next_facing = DIRECTIONS4
+            .iter()
+            .position(|&direction| next_face.direction_3d(direction) == -face.normal)
+            .unwrap();
+        (next_face.pos_2d(point, self.size), next_facing)
+    }
+}
+
+fn part1(notes: &Notes) -> usize {
+    password(notes.walk_traced("part 1", |state| notes.wrap_flat(state)))
+}
+
+fn part2(notes: &Notes) -> usize {
+    let cube = Cube::fold(&notes.map).unwrap_or_else(|err| panic!("cannot fold map into a cube: {}", err));
+    password(notes.walk_traced("part 2", |state| cube.wrap(state)))
 }
 
 #[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), 6032);
+fn cube_wrap() {
+    // Each map is the net of a cube with faces of size 2, with every tile open. Walking all the
+    // way around the cube in any direction from any position must return to the same state.
+    let nets = [
+        "    ..\n    ..\n......\n......\n    ....\n    ....\n",
+        "  ....\n  ....\n  ..\n  ..\n....\n....\n..\n..\n",
+        "  ..\n  ..\n......\n......\n  ..\n  ..\n  ..\n  ..\n",
+        "....\n....\n  ....\n  ....\n    ....\n    ....\n",
+    ];
+    for net in nets {
+        let map = Grid::parse(net).unwrap();
+        let cube = Cube::fold(&map).unwrap();
+        for (start, _) in map.iter().filter(|(_, &c)| c == OPEN) {
+            for facing in 0..4 {
+                let mut state = (start, facing);
+                for _ in 0..8 {
+                    let next = state.0 + DIRECTIONS4[state.1];
+                    state = if matches!(map.get(next), Some(&OPEN)) { (next, state.1) } else { cube.wrap(state) };
+                }
+                assert_eq!(state, (start, facing), "walking around {:?}", net);
+            }
+        }
+    }
+    assert!(Cube::fold(&Grid::parse("..\n..\n").unwrap()).is_err());
+    assert!(Cube::fold(&Grid::parse("  ..\n  ..\n......\n......\n  ....\n  ....\n").unwrap()).is_err());
 }
 
 #[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), 0);
+fn trace() {
+    let notes = Notes::parse(concat!(
+        "        ...#\n        .#..\n        #...\n        ....\n",
+        "...#.......#\n........#...\n..#....#....\n..........#.\n",
+        "        ...#....\n        .....#..\n        .#......\n        ......#.\n",
+        "\n10R5L5R10L4R5L5\n")).unwrap();
+    let trace = notes.trace(|state| notes.wrap_flat(state));
+    assert_eq!(trace.render(&notes.map, 3), concat!(
+        "        >>v#\n        .#v.\n        #.v.\n        ..v.\n",
+        "...#......v#\n........#.v.\n..#....#....\n..........#.\n",
+        "        ...#....\n        .....#..\n        .#......\n        ......#.\n"));
+    let frames = trace.frames(&notes.map, 4).collect::<Vec<_>>();
+    assert_eq!(frames.len(), 4);
+    assert_eq!(frames[3], concat!(
+        "        >>v#\n        .#v.\n        #.v.\n        ..v.\n",
+        "...#...v..v#\n>>>v...>#.>>\n..#v...#....\n...>>>>v..#.\n",
+        "        ...#....\n        .....#..\n        .#......\n        ......#.\n"));
+
+    let cube = Cube::fold(&notes.map).unwrap();
+    let trace = notes.trace(|state| cube.wrap(state));
+    assert_eq!(trace.render(&notes.map, 13), concat!(
+        "        >>v#\n        .#v.\n        #.v.\n        ..v.\n",
+        "...#..^...v#\n.>>>>>^.#.>>\n.^#....#....\n.^........#.\n",
+        "        ...#..v.\n        .....#v.\n        .#v<<<<.\n        ..v...#.\n"));
 }
 
-aoc::main!(run);
+aoc::main!(input_type = Notes, part1 = part1, part2 = part2, examples = [(0, (6032, 5031))]);
@@ -0,0 +1,8 @@
+//! Runs all days in a single process and prints a summary. Every `src/bin/NN.rs` file is included
+//! as a module by `build.rs`.
+
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+fn main() {
+    aoc::run_all(aoc::year!(), &solutions());
+}
@@ -1,15 +0,0 @@
-fn run(input: &str) -> usize {
-    0
-}
-
-#[test]
-fn examples() {
-    assert_eq!(run(&aoc::example!(0)), 0);
-}
-
-#[test]
-fn input() {
-    assert_eq!(run(&aoc::input!()), 0);
-}
-
-aoc::main!(run);
@@ -0,0 +1,175 @@
+//! A simple model of the devices in some puzzles: a CPU with a register file, which runs a program
+//! of instructions that each take some number of cycles, and peripherals that observe the
+//! registers during every cycle.
+//!
+//! The instruction set is not fixed: any type implementing `Op` can be used, so devices with other
+//! registers, jumps or conditional instructions can be modelled without changing the CPU.
+
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Register(pub usize);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Registers(Vec<i64>);
+
+impl Registers {
+    pub fn new(values: Vec<i64>) -> Self {
+        Self(values)
+    }
+}
+
+impl Index<Register> for Registers {
+    type Output = i64;
+
+    fn index(&self, register: Register) -> &i64 {
+        &self.0[register.0]
+    }
+}
+
+impl IndexMut<Register> for Registers {
+    fn index_mut(&mut self, register: Register) -> &mut i64 {
+        &mut self.0[register.0]
+    }
+}
+
+/// The part of the CPU's state that instructions can change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct State {
+    pub registers: Registers,
+    /// The index of the next instruction to run. When an instruction is executed, this already
+    /// points to the instruction after it, so jumps can simply overwrite it.
+    pub pc: usize,
+}
+
+/// An instruction of some instruction set.
+pub trait Op {
+    /// The number of cycles the instruction takes.
+    fn cycles(&self) -> i64;
+
+    /// Applies the instruction's effect, at the end of its last cycle.
+    fn execute(&self, state: &mut State);
+}
+
+/// A device that observes the registers during every cycle.
+pub trait Peripheral {
+    /// Called during each cycle, where `cycle` is the number of cycles that were completed before.
+    fn sample(&mut self, cycle: i64, registers: &Registers);
+}
+
+/// For running the CPU without any peripherals.
+impl Peripheral for () {
+    fn sample(&mut self, _cycle: i64, _registers: &Registers) {}
+}
+
+pub struct Cpu<I> {
+    program: Vec<I>,
+    state: State,
+    cycle: i64,
+    /// The index of the instruction that is running, and the cycle count at which it completes.
+    current: Option<(usize, i64)>,
+}
+
+impl<I: Op> Cpu<I> {
+    pub fn new(program: Vec<I>, registers: Registers) -> Self {
+        Self { program, state: State { registers, pc: 0 }, cycle: 0, current: None }
+    }
+
+    /// Runs a single cycle: starts the next instruction if none is running, lets `peripheral`
+    /// sample the registers, and executes the instruction if this was its last cycle. Returns
+    /// `false`, without running a cycle, once the program counter is past the end of the program.
+    pub fn tick(&mut self, peripheral: &mut impl Peripheral) -> bool {
+        if self.current.is_none() {
+            let Some(instruction) = self.program.get(self.state.pc) else {
+                return false;
+            };
+            self.current = Some((self.state.pc, self.cycle + instruction.cycles()));
+        }
+        peripheral.sample(self.cycle, &self.state.registers);
+        self.cycle += 1;
+        let (index, end) = self.current.unwrap();
+        if self.cycle >= end {
+            self.current = None;
+            self.state.pc = index + 1;
+            self.program[index].execute(&mut self.state);
+        }
+        true
+    }
+
+    /// Whether the program has ended, so that `tick` will return `false`.
+    pub fn is_done(&self) -> bool {
+        self.current.is_none() && self.state.pc >= self.program.len()
+    }
+
+    /// The number of cycles completed so far.
+    pub fn cycle(&self) -> i64 {
+        self.cycle
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.state.registers
+    }
+
+    /// The index of the instruction that is running, or that will run next.
+    pub fn pc(&self) -> usize {
+        self.current.map_or(self.state.pc, |(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: Register = Register(0);
+    const B: Register = Register(1);
+
+    /// A machine with two registers and a conditional jump, to check that the CPU isn't tied to
+    /// the CRT's instruction set.
+    enum Test {
+        Inc(Register),
+        /// Jumps to the given instruction if the register is less than the value.
+        JumpIfLess(Register, i64, usize),
+    }
+
+    impl Op for Test {
+        fn cycles(&self) -> i64 {
+            match self {
+                Test::Inc(_) => 1,
+                Test::JumpIfLess(..) => 3,
+            }
+        }
+
+        fn execute(&self, state: &mut State) {
+            match *self {
+                Test::Inc(register) => state.registers[register] += 1,
+                Test::JumpIfLess(register, value, target) => {
+                    if state.registers[register] < value {
+                        state.pc = target;
+                    }
+                },
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Vec<(i64, i64, i64)>);
+
+    impl Peripheral for Recorder {
+        fn sample(&mut self, cycle: i64, registers: &Registers) {
+            self.0.push((cycle, registers[A], registers[B]));
+        }
+    }
+
+    #[test]
+    fn test_custom_instructions() {
+        let program = vec![Test::Inc(A), Test::JumpIfLess(A, 3, 0), Test::Inc(B)];
+        let mut cpu = Cpu::new(program, Registers::new(vec![0, 10]));
+        let mut recorder = Recorder::default();
+        while cpu.tick(&mut recorder) {}
+        assert_eq!(cpu.registers(), &Registers::new(vec![3, 11]));
+        assert_eq!(cpu.cycle(), 3 * 4 + 1);
+        assert_eq!(recorder.0[..5], [(0, 0, 10), (1, 1, 10), (2, 1, 10), (3, 1, 10), (4, 1, 10)]);
+        assert_eq!(recorder.0.last(), Some(&(12, 3, 10)));
+        assert!(!cpu.tick(&mut ()));
+    }
+}
@@ -1,106 +1,265 @@
-use std::fmt::Write;
+use std::fmt::{self, Write};
 use std::str::FromStr;
 
-const ROWS: usize = 6;
-const COLS: usize = 40;
+use aoc::{Parse, ParseError};
 
+use crate::cpu::{Cpu, Op, Peripheral, Register, Registers, State};
+use crate::ocr::{self, OcrError};
+
+/// The CPU's only register.
+pub const X: Register = Register(0);
+
+/// The size of the screen and the sprite, and what happens when a program runs for more cycles than
+/// the screen has pixels. The default is the puzzle's 40x6 screen with a sprite 3 pixels wide. The
+/// screen must have at least one row and one column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Geometry {
+    pub rows: usize,
+    pub cols: usize,
+    /// The width of the sprite, which is centered on `X`. If the width is even, the sprite extends
+    /// one pixel further to the right than to the left. If it is 0, there is no sprite, so no
+    /// pixels are lit.
+    pub sprite_width: usize,
+    pub overflow: Overflow,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self { rows: 6, cols: 40, sprite_width: 3, overflow: Overflow::Ignore }
+    }
+}
+
+/// What to do with pixels drawn after the last pixel of the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Don't draw them.
+    Ignore,
+    /// Draw them from the top of the screen again, over the existing pixels.
+    Wrap,
+    /// Scroll the screen up by a row whenever a new row is started.
+    Scroll,
+    /// Halt the CRT before the cycle that would draw the pixel. See `Crt::error`.
+    Error,
+}
+
+/// The error that halted a CRT whose overflow policy is `Overflow::Error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverflowError {
+    /// The number of the cycle that would have drawn past the end of the screen, starting at 1.
+    pub cycle: i64,
+    pub geometry: Geometry,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "cycle {} would draw past the end of the {}x{} screen",
+            self.cycle, self.geometry.cols, self.geometry.rows)
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// The error returned when creating a screen with no rows or no columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmptyScreenError {
+    pub geometry: Geometry,
+}
+
+impl fmt::Display for EmptyScreenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the {}x{} screen has no pixels", self.geometry.cols, self.geometry.rows)
+    }
+}
+
+impl std::error::Error for EmptyScreenError {}
+
+/// The CRT: a CPU running a program, with a screen attached.
 pub struct Crt {
-    x: i64,
-    cycle: i64,
-    instr: Option<(i64, Instruction)>,
-    screen: [[u8; COLS]; ROWS],
+    cpu: Cpu<Instruction>,
+    screen: Screen,
+    error: Option<OverflowError>,
 }
 
 impl Crt {
-    pub fn new() -> Self {
-        Self {
-            x: 1,
-            cycle: 0,
-            instr: None,
-            screen: [[b'.'; COLS]; ROWS],
-        }
+    pub fn new(program: Vec<Instruction>) -> Self {
+        Self::with_geometry(program, Geometry::default()).unwrap()
     }
 
-    pub fn need_instr(&self) -> bool {
-        self.instr.is_none()
+    pub fn with_geometry(program: Vec<Instruction>, geometry: Geometry) -> Result<Self, EmptyScreenError> {
+        Ok(Self {
+            cpu: Cpu::new(program, Registers::new(vec![1])),
+            screen: Screen::new(geometry)?,
+            error: None,
+        })
     }
 
-    pub fn tick(&mut self, instrs: &mut impl Iterator<Item = Instruction>) -> bool {
-        self.draw_pixel();
-        if self.instr.is_none() {
-            if let Some(instr) = instrs.next() {
-                self.instr = Some((self.cycle + instr.duration(), instr));
-            } else {
-                return false;
-            }
+    /// Runs a single cycle, drawing a pixel. Returns `false` once the program has ended, or if the
+    /// CRT was halted because the pixel doesn't fit on the screen.
+    pub fn tick(&mut self) -> bool {
+        let geometry = self.screen.geometry;
+        let pixels = (geometry.rows * geometry.cols) as i64;
+        if geometry.overflow == Overflow::Error && self.cycle() >= pixels && !self.cpu.is_done() {
+            self.error = Some(OverflowError { cycle: self.cycle() + 1, geometry });
         }
-        self.cycle += 1;
-        if self.cycle >= self.instr.as_ref().unwrap().0 {
-            let instr = self.instr.take().unwrap().1;
-            self.complete(instr);
+        if self.error.is_some() {
+            return false;
         }
-        true
+        self.cpu.tick(&mut self.screen)
     }
 
-    fn draw_pixel(&mut self) {
-        let i = self.cycle as usize;
-        let row = i / COLS;
-        let col = i % COLS;
-        if row >= ROWS {
-            // Nowhere does it say how it would wrap around.
-            return;
+    /// Runs a single cycle, and returns what happened in it, or `None` once the program has ended.
+    pub fn step(&mut self) -> Option<CycleState> {
+        let x_during = self.x();
+        if !self.tick() {
+            return None;
         }
-        self.screen[row][col] =
-            if (self.x - col as i64).abs() <= 1 { b'#' } else { b'.' };
+        Some(CycleState { cycle: self.cycle(), x_during, x_after: self.x(), pixel: self.screen.last_pixel })
     }
 
-    fn complete(&mut self, instr: Instruction) {
-        use Instruction::*;
-        match instr {
-            Noop => {},
-            Addx(a) => { self.x += a; },
-        }
+    /// Runs the program to the end, yielding the state of each cycle.
+    pub fn cycles(&mut self) -> impl Iterator<Item = CycleState> + '_ {
+        std::iter::from_fn(|| self.step())
+    }
+
+    /// Why the CRT halted before the end of the program, if it did.
+    pub fn error(&self) -> Option<&OverflowError> {
+        self.error.as_ref()
+    }
+
+    pub fn cpu(&self) -> &Cpu<Instruction> {
+        &self.cpu
     }
 
     pub fn cycle(&self) -> i64 {
-        self.cycle
+        self.cpu.cycle()
     }
 
     pub fn x(&self) -> i64 {
-        self.x
+        self.cpu.registers()[X]
     }
 
     pub fn screen(&self) -> String {
-        let mut out = String::new();
-        for row in &self.screen {
-            write!(out, "{}\n", std::str::from_utf8(row).unwrap()).unwrap();
+        self.screen.to_string()
+    }
+
+    /// Reads the letters drawn on the screen.
+    pub fn letters(&self) -> Result<String, OcrError> {
+        ocr::recognize(&self.screen())
+    }
+}
+
+/// What happened during one cycle of the CRT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CycleState {
+    /// The number of the cycle, starting at 1.
+    pub cycle: i64,
+    /// The value of `X` during the cycle, which is what the puzzle's signal strength uses, and
+    /// what the screen uses to draw the pixel.
+    pub x_during: i64,
+    /// The value of `X` after the cycle, which differs from `x_during` if an instruction completed.
+    pub x_after: i64,
+    /// The pixel that was drawn, or `None` if it was off the screen.
+    pub pixel: Option<Pixel>,
+}
+
+/// A pixel drawn on the screen. The row is the row of the screen, which differs from the row of
+/// the image after wrapping or scrolling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pixel {
+    pub row: usize,
+    pub col: usize,
+    pub lit: bool,
+}
+
+/// The screen, which draws one pixel per cycle, lit if the sprite at position `X` covers it.
+pub struct Screen {
+    geometry: Geometry,
+    pixels: Vec<Vec<u8>>,
+    /// The number of rows scrolled off the top of the screen.
+    scrolled: usize,
+    last_pixel: Option<Pixel>,
+}
+
+impl Screen {
+    pub fn new(geometry: Geometry) -> Result<Self, EmptyScreenError> {
+        if geometry.rows == 0 || geometry.cols == 0 {
+            return Err(EmptyScreenError { geometry });
         }
-        out
+        Ok(Self {
+            geometry,
+            pixels: vec![vec![b'.'; geometry.cols]; geometry.rows],
+            scrolled: 0,
+            last_pixel: None,
+        })
+    }
+
+    /// The row of the screen to draw the pixel in the given row of the image on, if any.
+    fn screen_row(&mut self, row: usize) -> Option<usize> {
+        let rows = self.geometry.rows;
+        if row < rows {
+            return Some(row);
+        }
+        match self.geometry.overflow {
+            Overflow::Ignore | Overflow::Error => None,
+            Overflow::Wrap => Some(row % rows),
+            Overflow::Scroll => {
+                while row - self.scrolled >= rows {
+                    self.pixels.remove(0);
+                    self.pixels.push(vec![b'.'; self.geometry.cols]);
+                    self.scrolled += 1;
+                }
+                Some(row - self.scrolled)
+            },
+        }
+    }
+}
+
+impl Peripheral for Screen {
+    fn sample(&mut self, cycle: i64, registers: &Registers) {
+        let i = cycle as usize;
+        let col = i % self.geometry.cols;
+        self.last_pixel = self.screen_row(i / self.geometry.cols).map(|row| {
+            let width = self.geometry.sprite_width as i64;
+            let left = registers[X] - (width - 1).div_euclid(2);
+            let right = left + width - 1;
+            let lit = (left..=right).contains(&(col as i64));
+            self.pixels[row][col] = if lit { b'#' } else { b'.' };
+            Pixel { row, col, lit }
+        });
     }
 }
 
-#[derive(Debug, Clone)]
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.pixels {
+            writeln!(f, "{}", std::str::from_utf8(row).unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instruction {
     Noop,
     Addx(i64),
 }
 
-impl Instruction {
-    fn duration(&self) -> i64 {
+impl Op for Instruction {
+    fn cycles(&self) -> i64 {
         use Instruction::*;
         match self {
             Noop => 1,
             Addx(_) => 2,
         }
     }
-}
-
-#[derive(Debug)]
-pub struct ParseError(String);
 
-macro_rules! parse_error {
-    ($($arg:tt)*) => {
-        ParseError(format!($($arg)*))
+    fn execute(&self, state: &mut State) {
+        use Instruction::*;
+        match self {
+            Noop => {},
+            Addx(a) => { state.registers[X] += a; },
+        }
     }
 }
 
@@ -110,20 +269,109 @@ impl FromStr for Instruction {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use Instruction::*;
         let mut parts = s.split(' ');
-        let mnemonic = parts.next().ok_or_else(|| parse_error!("empty line"))?;
-        Ok(match mnemonic {
+        let mnemonic = parts.next().unwrap();
+        let instr = match mnemonic {
             "noop" => Noop,
-            "addx" => Addx(parse_i64(parts.next())?),
-            _ => return Err(parse_error!("unknown instruction {:?}", mnemonic)),
-        })
+            "addx" => Addx(parse_i64(s, parts.next())?),
+            _ => return Err(ParseError::expected(s, mnemonic, "instruction")),
+        };
+        match parts.next() {
+            Some(extra) => Err(ParseError::expected(s, extra, "end of line")),
+            None => Ok(instr),
+        }
     }
 }
 
-fn parse_i64(s: Option<&str>) -> Result<i64, ParseError> {
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Instruction::*;
+        match self {
+            Noop => write!(f, "noop"),
+            Addx(a) => write!(f, "addx {}", a),
+        }
+    }
+}
+
+/// Parses the operand `s`, if any, which is part of the instruction `line`.
+fn parse_i64(line: &str, s: Option<&str>) -> Result<i64, ParseError> {
     match s {
-        Some(s) => s.parse::<i64>()
-            .map_err(|err| parse_error!("invalid integer {:?}: {}", s, err)),
-        None => Err(parse_error!("expected integer, found end of line")),
+        Some(s) => s.parse::<i64>().map_err(|_| ParseError::expected(line, s, "integer")),
+        None => Err(ParseError::at(line, &line[line.len()..], "expected integer, found end of line")),
+    }
+}
+
+/// A program for the CPU, which may contain labels and comments in addition to the instructions.
+///
+/// Each line of a listing consists of an optional label followed by a colon, an optional
+/// instruction, and an optional comment starting with `;`:
+///
+/// ```text
+/// start:
+///     addx 15 ; x = 16
+///     noop
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    /// Each label, with the index of the instruction that follows it.
+    pub labels: Vec<(String, usize)>,
+}
+
+impl Parse for Program {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut program = Program::default();
+        for line in input.lines() {
+            let mut code = line.split(';').next().unwrap().trim();
+            if let Some((label, rest)) = code.split_once(':') {
+                let label = label.trim_end();
+                let is_identifier = label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !is_identifier {
+                    return Err(ParseError::expected(input, code, "label"));
+                }
+                if program.labels.iter().any(|(existing, _)| existing == label) {
+                    return Err(ParseError::at(input, label, format!("duplicate label {:?}", label)));
+                }
+                program.labels.push((label.to_owned(), program.instructions.len()));
+                code = rest.trim_start();
+            }
+            if !code.is_empty() {
+                let instruction = code.parse::<Instruction>().map_err(|err| err.relocate(input, code))?;
+                program.instructions.push(instruction);
+            }
+        }
+        Ok(program)
+    }
+}
+
+impl Program {
+    /// Writes a listing with the labels, and with each instruction annotated with the cycle that it
+    /// starts on. Parsing the listing results in the same program.
+    pub fn disassemble(&self) -> String {
+        let mut out = String::new();
+        let mut labels = self.labels.iter().peekable();
+        let mut cycle = 1;
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            while let Some((label, _)) = labels.next_if(|(_, index)| *index == i) {
+                writeln!(out, "{}:", label).unwrap();
+            }
+            writeln!(out, "    {:<12}; cycle {}", instruction.to_string(), cycle).unwrap();
+            cycle += instruction.cycles();
+        }
+        for (label, _) in labels {
+            writeln!(out, "{}:", label).unwrap();
+        }
+        out
+    }
+}
+
+/// Writes the instructions only, one per line, like the puzzle input.
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+        Ok(())
     }
 }
 
@@ -133,26 +381,142 @@ mod tests {
 
     #[test]
     fn test_duration() {
-        let mut instrs = "noop\naddx 3\naddx -5"
-            .lines()
-            .map(|line| line.parse::<Instruction>().unwrap());
-        let mut crt = Crt::new();
+        let program = Program::parse("noop\naddx 3\naddx -5").unwrap();
+        let mut crt = Crt::new(program.instructions);
         assert_eq!(crt.cycle(), 0);
         assert_eq!(crt.x(), 1);
-        crt.tick(&mut instrs);
+        crt.tick();
         assert_eq!(crt.cycle(), 1);
         assert_eq!(crt.x(), 1);
-        crt.tick(&mut instrs);
+        crt.tick();
         assert_eq!(crt.cycle(), 2);
         assert_eq!(crt.x(), 1);
-        crt.tick(&mut instrs);
+        crt.tick();
         assert_eq!(crt.cycle(), 3);
         assert_eq!(crt.x(), 4);
-        crt.tick(&mut instrs);
+        crt.tick();
         assert_eq!(crt.cycle(), 4);
         assert_eq!(crt.x(), 4);
-        crt.tick(&mut instrs);
+        crt.tick();
         assert_eq!(crt.cycle(), 5);
         assert_eq!(crt.x(), -1);
     }
+
+    #[test]
+    fn test_cycles() {
+        let program = Program::parse("noop\naddx 3\naddx -5\n").unwrap();
+        let states = Crt::new(program.instructions)
+            .cycles()
+            .map(|state| (state.cycle, state.x_during, state.x_after))
+            .collect::<Vec<_>>();
+        assert_eq!(states, [(1, 1, 1), (2, 1, 1), (3, 1, 4), (4, 4, 4), (5, 4, -1)]);
+
+        let program = Program::parse(&"noop\n".repeat(242)).unwrap();
+        let pixels = Crt::new(program.instructions).cycles().map(|state| state.pixel).collect::<Vec<_>>();
+        assert_eq!(pixels[0], Some(Pixel { row: 0, col: 0, lit: true }));
+        assert_eq!(pixels[3], Some(Pixel { row: 0, col: 3, lit: false }));
+        assert_eq!(pixels[41], Some(Pixel { row: 1, col: 1, lit: true }));
+        assert_eq!(pixels[240], None);
+    }
+
+    #[test]
+    fn test_geometry() {
+        // Draws ".#.." twice, then ".#.#".
+        let program = Program::parse(&("noop\n".repeat(8) + "addx 2\nnoop\nnoop\n")).unwrap();
+        let crt = |overflow| {
+            let geometry = Geometry { rows: 2, cols: 4, sprite_width: 1, overflow };
+            Crt::with_geometry(program.instructions.clone(), geometry).unwrap()
+        };
+
+        let mut ignore = crt(Overflow::Ignore);
+        assert_eq!(ignore.cycles().filter(|state| state.pixel.is_none()).count(), 4);
+        assert_eq!(ignore.screen(), ".#..\n.#..\n");
+
+        let mut wrap = crt(Overflow::Wrap);
+        assert_eq!(wrap.cycles().last().unwrap().pixel, Some(Pixel { row: 0, col: 3, lit: true }));
+        assert_eq!(wrap.screen(), ".#.#\n.#..\n");
+
+        let mut scroll = crt(Overflow::Scroll);
+        assert_eq!(scroll.cycles().last().unwrap().pixel, Some(Pixel { row: 1, col: 3, lit: true }));
+        assert_eq!(scroll.screen(), ".#..\n.#.#\n");
+
+        let mut error = crt(Overflow::Error);
+        assert_eq!(error.cycles().count(), 8);
+        let err = error.error().unwrap();
+        assert_eq!(err.cycle, 9);
+        assert_eq!(err.to_string(), "cycle 9 would draw past the end of the 4x2 screen");
+
+        // A program that exactly fills the screen is fine.
+        let program = Program::parse(&"noop\n".repeat(8)).unwrap();
+        let geometry = Geometry { rows: 1, cols: 8, sprite_width: 4, overflow: Overflow::Error };
+        let mut crt = Crt::with_geometry(program.instructions.clone(), geometry).unwrap();
+        assert_eq!(crt.cycles().count(), 8);
+        assert_eq!(crt.screen(), "####....\n");
+        assert_eq!(crt.error(), None);
+
+        let geometry = Geometry { sprite_width: 0, ..geometry };
+        let mut crt = Crt::with_geometry(program.instructions.clone(), geometry).unwrap();
+        assert_eq!(crt.cycles().count(), 8);
+        assert_eq!(crt.screen(), "........\n");
+
+        for (rows, cols) in [(0, 8), (1, 0)] {
+            let geometry = Geometry { rows, cols, ..geometry };
+            let err = Crt::with_geometry(program.instructions.clone(), geometry).err().unwrap();
+            assert_eq!(err, EmptyScreenError { geometry });
+        }
+        let geometry = Geometry { rows: 0, ..Geometry::default() };
+        assert_eq!(EmptyScreenError { geometry }.to_string(), "the 40x0 screen has no pixels");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Vec::<Instruction>::parse("noop\naddx 1 2\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 8));
+        assert_eq!(err.message, "expected end of line, found \"2\"");
+        let err = Vec::<Instruction>::parse("noop\naddx\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        assert_eq!(err.message, "expected integer, found end of line");
+        let err = Vec::<Instruction>::parse("addx -x\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 6));
+        assert_eq!(err.message, "expected integer, found \"-x\"");
+        let err = Vec::<Instruction>::parse("noop\nnoop\nsubx 1\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        assert_eq!(err.to_string(), "line 3, column 1: expected instruction, found \"subx\"\n  |\n3 | subx 1\n  | ^");
+    }
+
+    #[test]
+    fn test_display() {
+        let input = "noop\naddx 3\naddx -5\n";
+        let program = Program::parse(input).unwrap();
+        assert_eq!(program.instructions, [Instruction::Noop, Instruction::Addx(3), Instruction::Addx(-5)]);
+        assert_eq!(program.to_string(), input);
+    }
+
+    #[test]
+    fn test_listing() {
+        let listing = "start:\n    noop ; wait\n\nloop: addx 3\n  addx -5\nend:\n";
+        let program = Program::parse(listing).unwrap();
+        assert_eq!(program.instructions, [Instruction::Noop, Instruction::Addx(3), Instruction::Addx(-5)]);
+        assert_eq!(program.labels, [("start".to_owned(), 0), ("loop".to_owned(), 1), ("end".to_owned(), 3)]);
+        assert_eq!(program.to_string(), "noop\naddx 3\naddx -5\n");
+
+        let disassembly = program.disassemble();
+        assert_eq!(
+            disassembly,
+            "start:\n    noop        ; cycle 1\nloop:\n    addx 3      ; cycle 2\n    addx -5     ; cycle 4\nend:\n");
+        assert_eq!(Program::parse(&disassembly), Ok(program));
+    }
+
+    #[test]
+    fn test_listing_errors() {
+        let err = Program::parse("start:\n  noop\n  addx ; missing\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 7));
+        assert_eq!(err.message, "expected integer, found end of line");
+        let err = Program::parse("a:\nnoop\n  a: noop\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.message, "duplicate label \"a\"");
+        let err = Program::parse("1st: noop\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+        assert_eq!(err.message, "expected label, found \"1st:\"");
+    }
 }
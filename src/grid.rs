@@ -0,0 +1,274 @@
+//! A two-dimensional grid of cells, as found in many puzzle inputs.
+//!
+//! Cells are addressed by an `IVec2` coordinate, where `x` is the column and `y` is the row, both
+//! counting from 0 at the top left. So `y` increases downwards, as in the input text.
+//!
+//! Rows may have different lengths, like in a map where trailing spaces have been trimmed. Cells
+//! beyond the end of a row are not part of the grid: `get` returns `None` for them, just like for
+//! coordinates outside the grid's bounding box.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use aoc::{Parse, ParseError};
+use glam::IVec2;
+
+pub const RIGHT: IVec2 = IVec2::new(1, 0);
+pub const DOWN: IVec2 = IVec2::new(0, 1);
+pub const LEFT: IVec2 = IVec2::new(-1, 0);
+pub const UP: IVec2 = IVec2::new(0, -1);
+
+/// The four orthogonal directions, clockwise starting from `RIGHT`.
+pub const DIRECTIONS4: [IVec2; 4] = [RIGHT, DOWN, LEFT, UP];
+
+/// The four orthogonal and four diagonal directions, clockwise starting from `RIGHT`.
+pub const DIRECTIONS8: [IVec2; 8] = [
+    RIGHT, IVec2::new(1, 1), DOWN, IVec2::new(-1, 1), LEFT, IVec2::new(-1, -1), UP, IVec2::new(1, -1),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    rows: Vec<Vec<T>>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a rectangular grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self where T: Clone {
+        Self { width, rows: vec![vec![value; width]; height] }
+    }
+
+    /// Creates a grid from its rows, which may differ in length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        Self { width, rows }
+    }
+
+    /// Parses a grid from text, one row per line, converting each character using `cell`. Fails at
+    /// the first character for which `cell` returns `None`.
+    pub fn parse_with(input: &str, mut cell: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        let rows = input
+            .lines()
+            .map(|line| {
+                line.char_indices()
+                    .map(|(i, c)| {
+                        let location = &line[i..];
+                        cell(c).ok_or_else(|| ParseError::at(input, location, format!("unexpected {:?}", c)))
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::from_rows(rows))
+    }
+
+    /// The length of the longest row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether `coord` refers to a cell of the grid.
+    pub fn contains(&self, coord: IVec2) -> bool {
+        self.get(coord).is_some()
+    }
+
+    pub fn get(&self, coord: IVec2) -> Option<&T> {
+        let row = self.rows.get(usize::try_from(coord.y).ok()?)?;
+        row.get(usize::try_from(coord.x).ok()?)
+    }
+
+    pub fn get_mut(&mut self, coord: IVec2) -> Option<&mut T> {
+        let row = self.rows.get_mut(usize::try_from(coord.y).ok()?)?;
+        row.get_mut(usize::try_from(coord.x).ok()?)
+    }
+
+    /// Wraps `coord` around the edges of the grid's bounding box, so that it lies within it.
+    /// Returns `None` if the grid is empty, so there is nothing to wrap to.
+    pub fn wrap(&self, coord: IVec2) -> Option<IVec2> {
+        if self.width == 0 || self.height() == 0 {
+            return None;
+        }
+        Some(IVec2::new(coord.x.rem_euclid(self.width as i32), coord.y.rem_euclid(self.height() as i32)))
+    }
+
+    /// Like `get`, but wraps `coord` around the edges of the grid first. Can still return `None` if
+    /// the wrapped coordinate lies beyond the end of a short row.
+    pub fn get_wrapping(&self, coord: IVec2) -> Option<&T> {
+        self.get(self.wrap(coord)?)
+    }
+
+    /// Returns the coordinate of the first cell, in reading order, that satisfies `predicate`.
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<IVec2> {
+        self.iter().find(|(_, cell)| predicate(cell)).map(|(coord, _)| coord)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.rows.iter().map(|row| row.as_slice())
+    }
+
+    /// Iterates over the cells in column `x`, from top to bottom, skipping rows that are too
+    /// short to have that column.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.rows.iter().filter_map(move |row| row.get(x))
+    }
+
+    /// Iterates over all coordinates and cells, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        self.rows.iter().enumerate().flat_map(|(y, row)| {
+            row.iter().enumerate().map(move |(x, cell)| (IVec2::new(x as i32, y as i32), cell))
+        })
+    }
+
+    /// Iterates over the coordinates and cells starting at `start` and repeatedly stepping in
+    /// `direction`, until leaving the grid. Includes `start` itself if it's in the grid.
+    pub fn walk(&self, start: IVec2, direction: IVec2) -> impl Iterator<Item = (IVec2, &T)> {
+        let mut coord = start;
+        std::iter::from_fn(move || {
+            let cell = self.get(coord)?;
+            let item = (coord, cell);
+            coord += direction;
+            Some(item)
+        })
+    }
+
+    /// The coordinates of the orthogonally adjacent cells of `coord` that are in the grid.
+    pub fn neighbors4(&self, coord: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        self.neighbors(coord, &DIRECTIONS4)
+    }
+
+    /// The coordinates of the orthogonally and diagonally adjacent cells of `coord` that are in
+    /// the grid.
+    pub fn neighbors8(&self, coord: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        self.neighbors(coord, &DIRECTIONS8)
+    }
+
+    fn neighbors<'a>(&'a self, coord: IVec2, directions: &'static [IVec2])
+        -> impl Iterator<Item = IVec2> + 'a
+    {
+        directions
+            .iter()
+            .map(move |&direction| coord + direction)
+            .filter(|&neighbor| self.contains(neighbor))
+    }
+
+    /// Creates a grid of the same shape, with each cell converted by `f`.
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            rows: self.rows.iter().map(|row| row.iter().map(&mut f).collect()).collect(),
+        }
+    }
+
+    /// Renders the grid as text, one line per row, converting each cell using `cell`.
+    pub fn render(&self, mut cell: impl FnMut(IVec2, &T) -> char) -> String {
+        let mut out = String::new();
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                out.push(cell(IVec2::new(x as i32, y as i32), value));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T> Index<IVec2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: IVec2) -> &T {
+        self.get(coord).unwrap_or_else(|| panic!("coordinate {} is not in the grid", coord))
+    }
+}
+
+impl<T> IndexMut<IVec2> for Grid<T> {
+    fn index_mut(&mut self, coord: IVec2) -> &mut T {
+        self.get_mut(coord).unwrap_or_else(|| panic!("coordinate {} is not in the grid", coord))
+    }
+}
+
+/// Parses each line of the input as a row of bytes.
+impl Parse for Grid<u8> {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self::from_rows(input.lines().map(|line| line.as_bytes().to_vec()).collect()))
+    }
+}
+
+impl fmt::Display for Grid<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(|_, &cell| cell as char))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "  .#\n..#.\n.\n";
+
+    #[test]
+    fn test_parse_render() {
+        let grid = Grid::parse(MAP).unwrap();
+        assert_eq!((grid.width(), grid.height()), (4, 3));
+        assert_eq!(grid.to_string(), MAP);
+
+        let grid = Grid::parse_with("#.\n.#\n", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }).unwrap();
+        assert_eq!(grid.render(|_, &wall| if wall { '#' } else { '.' }), "#.\n.#\n");
+        let err = Grid::parse_with("#.\n.x\n", |c| (c == '#' || c == '.').then_some(c)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(err.message, "unexpected 'x'");
+    }
+
+    #[test]
+    fn test_get() {
+        let grid = Grid::parse(MAP).unwrap();
+        assert_eq!(grid.get(IVec2::new(3, 0)), Some(&b'#'));
+        assert_eq!(grid[IVec2::new(0, 2)], b'.');
+        assert_eq!(grid.get(IVec2::new(1, 2)), None);
+        assert_eq!(grid.get(IVec2::new(-1, 0)), None);
+        assert_eq!(grid.get(IVec2::new(0, 3)), None);
+        assert_eq!(grid.wrap(IVec2::new(-1, 3)), Some(IVec2::new(3, 0)));
+        assert_eq!(grid.get_wrapping(IVec2::new(4, -2)), Some(&b'.'));
+        assert_eq!(grid.get_wrapping(IVec2::new(5, -1)), None);
+        assert_eq!(grid.position(|&cell| cell == b'#'), Some(IVec2::new(3, 0)));
+    }
+
+    #[test]
+    fn test_iterators() {
+        let grid = Grid::parse(MAP).unwrap();
+        assert_eq!(grid.rows().nth(1), Some(&b"..#."[..]));
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), b" .");
+        assert_eq!(
+            grid.walk(IVec2::new(3, 1), LEFT).map(|(_, &cell)| cell).collect::<Vec<_>>(),
+            b".#..");
+        assert_eq!(grid.walk(IVec2::new(0, 0), DOWN).count(), 3);
+        assert_eq!(grid.iter().count(), 9);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 2, 0);
+        assert_eq!(
+            grid.neighbors4(IVec2::new(0, 0)).collect::<Vec<_>>(),
+            [IVec2::new(1, 0), IVec2::new(0, 1)]);
+        assert_eq!(grid.neighbors4(IVec2::new(1, 1)).count(), 3);
+        assert_eq!(grid.neighbors8(IVec2::new(1, 1)).count(), 5);
+        assert_eq!(grid.neighbors8(IVec2::new(1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn test_empty() {
+        let grid = Grid::<u8>::from_rows(Vec::new());
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        assert_eq!(grid.wrap(IVec2::new(1, 1)), None);
+        assert_eq!(grid.get_wrapping(IVec2::new(1, 1)), None);
+        let grid = Grid::new(0, 2, 0);
+        assert_eq!(grid.wrap(IVec2::ZERO), None);
+    }
+}
@@ -1 +1,7 @@
+pub mod cpu;
 pub mod crt;
+pub mod grid;
+pub mod ocr;
+pub mod parse;
+pub mod search;
+pub mod trace;
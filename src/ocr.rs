@@ -0,0 +1,110 @@
+//! Recognition of the capital letters that some puzzles draw on a screen, like day 10's CRT.
+//!
+//! The letters are 4 pixels wide and 6 high, with one column of space between them. Lit pixels are
+//! `#` and dark pixels are `.`.
+
+use std::error::Error as StdError;
+use std::fmt;
+
+const HEIGHT: usize = 6;
+const WIDTH: usize = 4;
+const STRIDE: usize = WIDTH + 1;
+
+const FONT: [(char, [&str; HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OcrError {
+    /// The screen is not 6 rows high.
+    Height(usize),
+    /// The cell starting at the given column does not contain a known letter.
+    UnknownGlyph { column: usize, glyph: Vec<String> },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Height(height) => {
+                write!(f, "screen is {} rows high, but letters are {} rows high", height, HEIGHT)
+            },
+            OcrError::UnknownGlyph { column, glyph } => {
+                write!(f, "unrecognized letter at column {}:", column)?;
+                for row in glyph {
+                    write!(f, "\n{}", row)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl StdError for OcrError {}
+
+/// Reads the letters from a screen, given as lines of `#` and `.`. Lines may have trailing
+/// whitespace.
+pub fn recognize(screen: &str) -> Result<String, OcrError> {
+    let rows = screen.lines().map(str::trim_end).collect::<Vec<_>>();
+    if rows.len() != HEIGHT {
+        return Err(OcrError::Height(rows.len()));
+    }
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    (0..width)
+        .step_by(STRIDE)
+        .map(|column| {
+            let glyph = rows
+                .iter()
+                .map(|row| {
+                    (column..column + WIDTH)
+                        .map(|x| row.as_bytes().get(x).map_or('.', |&pixel| pixel as char))
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            FONT.iter()
+                .find(|(_, pixels)| pixels.iter().zip(&glyph).all(|(a, b)| a == b))
+                .map(|&(letter, _)| letter)
+                .ok_or(OcrError::UnknownGlyph { column, glyph })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognize() {
+        let screen = "\
+            ####..##..#.....##..#..#.#....###...##..\n\
+            #....#..#.#....#..#.#..#.#....#..#.#..#.\n\
+            ###..#..#.#....#....#..#.#....#..#.#....\n\
+            #....####.#....#.##.#..#.#....###..#.##.\n\
+            #....#..#.#....#..#.#..#.#....#....#..#.\n\
+            ####.#..#.####..###..##..####.#.....###.\n";
+        assert_eq!(recognize(screen), Ok("EALGULPG".to_owned()));
+        assert_eq!(recognize("#..#\n#..#\n####\n#..#\n#..#\n#..#"), Ok("H".to_owned()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(recognize("####\n"), Err(OcrError::Height(1)));
+        let err = recognize("#..#.#..#\n#..#.#..#\n####.##.#\n#..#.#.##\n#..#.#..#\n#..#.#..#\n").unwrap_err();
+        assert_eq!(err.to_string(), "unrecognized letter at column 5:\n#..#\n#..#\n##.#\n#.##\n#..#\n#..#");
+    }
+}
@@ -0,0 +1,269 @@
+//! Helpers for parsing puzzle inputs with nom, and reporting failures as `aoc::ParseError`.
+//!
+//! The combinators here are generic over the nom error type, so they can be used with both
+//! `nom::error::Error` and `nom::error::VerboseError`. Parsers for lines consume the line ending,
+//! if any, so that they can be repeated with `lines` and `blocks`.
+
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use aoc::ParseError;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, digit1, line_ending, one_of, space0, space1};
+use nom::combinator::{cut, eof, opt, recognize};
+use nom::error::{ErrorKind, ParseError as NomParseError, VerboseError, VerboseErrorKind};
+use nom::multi::{many1, separated_list1};
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
+use nom::{IResult, Parser};
+
+/// Parses an unsigned integer, e.g. `42`.
+pub fn unsigned<'a, T: FromStr, E: NomParseError<&'a str>>(input: &'a str) -> IResult<&'a str, T, E> {
+    from_str(recognize(digit1))(input)
+}
+
+/// Parses an integer with an optional sign, e.g. `-42` or `+42`.
+pub fn signed<'a, T: FromStr, E: NomParseError<&'a str>>(input: &'a str) -> IResult<&'a str, T, E> {
+    from_str(recognize(pair(opt(one_of("+-")), digit1)))(input)
+}
+
+/// Parses the text recognized by `parser` using `FromStr`, failing if that fails, e.g. because
+/// the number is too large for the type.
+fn from_str<'a, T: FromStr, E: NomParseError<&'a str>>(mut parser: impl Parser<&'a str, &'a str, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, T, E>
+{
+    move |input| {
+        let (rest, text) = parser.parse(input)?;
+        match text.parse() {
+            Ok(value) => Ok((rest, value)),
+            Err(_) => Err(nom::Err::Error(E::from_error_kind(input, ErrorKind::Digit))),
+        }
+    }
+}
+
+/// Parses an inclusive range of unsigned integers, e.g. `2-4`.
+pub fn range<'a, T: FromStr, E: NomParseError<&'a str>>(input: &'a str)
+    -> IResult<&'a str, RangeInclusive<T>, E>
+{
+    let (rest, (start, end)) = separated_pair(unsigned, char('-'), unsigned)(input)?;
+    Ok((rest, start..=end))
+}
+
+/// Parses one or more items separated by commas, optionally surrounded by spaces, e.g. `1, 2,3`.
+pub fn comma_list<'a, O, E: NomParseError<&'a str>>(item: impl Parser<&'a str, O, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E>
+{
+    separated_list1(tuple((space0, char(','), space0)), item)
+}
+
+/// Parses one or more items separated by spaces, e.g. `1 2  3`.
+pub fn space_list<'a, O, E: NomParseError<&'a str>>(item: impl Parser<&'a str, O, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E>
+{
+    separated_list1(space1, item)
+}
+
+/// Parses a line using `parser`, followed by a line ending or the end of the input.
+pub fn line<'a, O, E: NomParseError<&'a str>>(parser: impl Parser<&'a str, O, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+{
+    terminated(parser, alt((line_ending, eof)))
+}
+
+/// Parses one or more lines, each using `parser`.
+pub fn lines<'a, O, E: NomParseError<&'a str>>(parser: impl Parser<&'a str, O, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E>
+{
+    many1(line(parser))
+}
+
+/// Parses a line of the form `key: value`, which may be indented, e.g. `  Test: divisible by 23`.
+/// Spaces after the colon are skipped before the value is parsed.
+pub fn key_value<'a, O, E: NomParseError<&'a str>>(key: &'static str, value: impl Parser<&'a str, O, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+{
+    line(preceded(delimited(space0, tag(key), pair(char(':'), space0)), value))
+}
+
+/// Parses one or more blocks separated by blank lines. Each block must consume its own final line
+/// ending, e.g. by using `lines`. A blank line must be followed by another block, so that an error
+/// in a block is reported where it occurs rather than at the preceding blank line.
+pub fn blocks<'a, O, E: NomParseError<&'a str>>(mut block: impl Parser<&'a str, O, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E>
+{
+    move |input| {
+        let (mut rest, first) = block.parse(input)?;
+        let mut blocks = vec![first];
+        while let Ok((after_blank, _)) = line_ending::<_, E>(rest) {
+            let (after_block, next) = cut(|input| block.parse(input))(after_blank)?;
+            blocks.push(next);
+            rest = after_block;
+        }
+        Ok((rest, blocks))
+    }
+}
+
+/// Parses a grid of cells, one row per line, e.g. `grid(one_of(".#"))`. Rows may differ in
+/// length.
+pub fn grid<'a, O, E: NomParseError<&'a str>>(cell: impl Parser<&'a str, O, E>)
+    -> impl FnMut(&'a str) -> IResult<&'a str, Vec<Vec<O>>, E>
+{
+    lines(many1(cell))
+}
+
+/// Runs the parser on the entire input. Fails if the parser fails, or if it doesn't consume all of
+/// the input.
+pub fn parse_all<'a, O, E: NomError<'a>>(
+    input: &'a str, mut parser: impl FnMut(&'a str) -> IResult<&'a str, O, E>)
+    -> Result<O, ParseError>
+{
+    finish(input, parser(input))
+}
+
+/// Converts the result of running a nom parser on `input` into a `ParseError` if it failed or did
+/// not consume all of the input.
+pub fn finish<'a, O, E: NomError<'a>>(input: &'a str, result: IResult<&'a str, O, E>)
+    -> Result<O, ParseError>
+{
+    match result {
+        Ok(("", output)) => Ok(output),
+        Ok((rest, _)) => Err(ParseError::expected(input, rest, "end of input")),
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => Err(err.into_parse_error(input)),
+        Err(nom::Err::Incomplete(_)) => {
+            Err(ParseError::expected(input, &input[input.len()..], "more input"))
+        },
+    }
+}
+
+/// A nom error type that can be converted into an `aoc::ParseError`.
+pub trait NomError<'a> {
+    /// Converts this error, which occurred while parsing somewhere in `input`.
+    fn into_parse_error(self, input: &'a str) -> ParseError;
+}
+
+impl<'a> NomError<'a> for nom::error::Error<&'a str> {
+    fn into_parse_error(self, input: &'a str) -> ParseError {
+        ParseError::expected(input, self.input, describe_kind(self.code))
+    }
+}
+
+/// The location is that of the innermost error, which is the most precise. The expectation is
+/// taken from the innermost `context`, if any, because that is the most readable.
+impl<'a> NomError<'a> for VerboseError<&'a str> {
+    fn into_parse_error(self, input: &'a str) -> ParseError {
+        let Some((location, kind)) = self.errors.first() else {
+            return ParseError::at(input, input, "invalid input");
+        };
+        let expected = self.errors
+            .iter()
+            .find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Context(context) => Some(context.to_string()),
+                _ => None,
+            })
+            .unwrap_or_else(|| match kind {
+                VerboseErrorKind::Context(context) => context.to_string(),
+                VerboseErrorKind::Char(c) => format!("{:?}", c),
+                VerboseErrorKind::Nom(kind) => describe_kind(*kind),
+            });
+        ParseError::expected(input, location, expected)
+    }
+}
+
+fn describe_kind(kind: ErrorKind) -> String {
+    match kind {
+        ErrorKind::Tag => "literal text".to_owned(),
+        ErrorKind::Digit => "digits".to_owned(),
+        ErrorKind::Alpha => "letters".to_owned(),
+        ErrorKind::AlphaNumeric => "letters or digits".to_owned(),
+        ErrorKind::Space | ErrorKind::MultiSpace => "whitespace".to_owned(),
+        ErrorKind::CrLf => "end of line".to_owned(),
+        ErrorKind::Eof => "end of input".to_owned(),
+        kind => kind.description().to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use nom::character::complete::{alpha1, none_of};
+    use nom::error::context;
+
+    type Error<'a> = nom::error::Error<&'a str>;
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(unsigned::<u32, Error>("42 rest"), Ok((" rest", 42)));
+        assert!(unsigned::<u32, Error>("-42").is_err());
+        assert!(unsigned::<u8, Error>("256").is_err());
+        assert_eq!(signed::<i64, Error>("-42"), Ok(("", -42)));
+        assert_eq!(signed::<i64, Error>("+42"), Ok(("", 42)));
+        assert!(signed::<i64, Error>("- 42").is_err());
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(range::<u64, Error>("2-4,6-8"), Ok((",6-8", 2..=4)));
+        assert!(range::<u64, Error>("2..4").is_err());
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(comma_list::<_, Error>(unsigned::<u32, _>)("79, 98,1"), Ok(("", vec![79, 98, 1])));
+        assert_eq!(space_list::<_, Error>(signed::<i32, _>)("1  -2 3\n"), Ok(("\n", vec![1, -2, 3])));
+    }
+
+    #[test]
+    fn test_lines() {
+        let input = "1000\n2000\n\n4000\n";
+        assert_eq!(
+            parse_all(input, blocks(lines(unsigned::<u32, Error>))),
+            Ok(vec![vec![1000, 2000], vec![4000]]));
+        assert_eq!(
+            parse_all(input.trim_end(), blocks(lines(unsigned::<u32, Error>))),
+            Ok(vec![vec![1000, 2000], vec![4000]]));
+        let err = parse_all("1000\n\n4x00\n", blocks(lines(unsigned::<u32, Error>))).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 2));
+
+        let (rest, value) = key_value::<_, Error>("Starting items", comma_list(unsigned::<u32, _>))(
+            "  Starting items: 79, 98\n  Operation: new = old * 19\n").unwrap();
+        assert_eq!(value, vec![79, 98]);
+        assert_eq!(rest, "  Operation: new = old * 19\n");
+        assert!(key_value::<_, Error>("Test", alpha1)("  Text: x\n").is_err());
+    }
+
+    #[test]
+    fn test_grid() {
+        assert_eq!(
+            parse_all("  .#\n#..\n", grid(none_of::<_, _, Error>("\r\n"))),
+            Ok(vec![vec![' ', ' ', '.', '#'], vec!['#', '.', '.']]));
+        let err = parse_all("..\n.x\n", grid(one_of::<_, _, Error>(".#"))).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.message, "expected end of input, found \".x\"");
+    }
+
+    #[test]
+    fn test_simple_error() {
+        let input = "addx 3\nadd 4\n";
+        let err = parse_all(input, preceded(tag::<_, _, nom::error::Error<_>>("addx 3\n"), tag("addx")))
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.message, "expected literal text, found \"add\"");
+    }
+
+    #[test]
+    fn test_verbose_error() {
+        let input = "1-3\n5+7\n";
+        let range = |input| context(
+            "range",
+            separated_pair(digit1::<_, VerboseError<_>>, char('-'), digit1))(input);
+        let err = parse_all(input, separated_pair(range, char('\n'), range)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(err.message, "expected range, found \"+7\"");
+
+        let err = parse_all(input, preceded(tag::<_, _, VerboseError<_>>("1-3"), tag("\n5+7")))
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+        assert_eq!(err.message, "expected end of input, found end of line");
+    }
+}
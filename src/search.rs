@@ -0,0 +1,255 @@
+//! Shortest path searches over arbitrary state spaces, such as grid coordinates or game states.
+//!
+//! The graph is given implicitly by a successor function, so it's never materialized, and the
+//! puzzle input is never modified. Each search starts from any number of source states at distance
+//! zero, and stops as soon as the shortest path to a goal state is known. To compute distances to
+//! all reachable states instead, pass `|_| false` as the goal.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The outcome of a search: the distances to all states whose shortest path has been determined,
+/// and the last step of each of those paths.
+#[derive(Clone, Debug)]
+pub struct Search<S, C = usize> {
+    distances: HashMap<S, C>,
+    predecessors: HashMap<S, S>,
+    goal: Option<S>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Search<S, C> {
+    /// The goal state that was reached, if any. If several goal states are equally close, this is
+    /// one of them.
+    pub fn goal(&self) -> Option<&S> {
+        self.goal.as_ref()
+    }
+
+    /// The distance to the goal state, if one was reached.
+    pub fn goal_distance(&self) -> Option<C> {
+        self.distance(self.goal.as_ref()?)
+    }
+
+    /// The length of the shortest path from any source to `state`, if it was determined.
+    pub fn distance(&self, state: &S) -> Option<C> {
+        self.distances.get(state).copied()
+    }
+
+    pub fn distances(&self) -> &HashMap<S, C> {
+        &self.distances
+    }
+
+    /// The state before `state` on the shortest path to it, or `None` if `state` is a source or
+    /// its shortest path was not determined.
+    pub fn predecessor(&self, state: &S) -> Option<&S> {
+        self.predecessors.get(state)
+    }
+
+    pub fn predecessors(&self) -> &HashMap<S, S> {
+        &self.predecessors
+    }
+
+    /// The shortest path from a source to `state`, including both, or `None` if it was not
+    /// determined.
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        self.distances.get(state)?;
+        let mut path = vec![state.clone()];
+        while let Some(predecessor) = self.predecessors.get(path.last().unwrap()) {
+            path.push(predecessor.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The shortest path from a source to the goal state, if one was reached.
+    pub fn goal_path(&self) -> Option<Vec<S>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+}
+
+/// Breadth-first search, for graphs where every step has the same cost. Distances count steps.
+pub fn bfs<S, I>(
+    sources: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut search = Search { distances: HashMap::new(), predecessors: HashMap::new(), goal: None };
+    let mut queue = VecDeque::new();
+    for source in sources {
+        if let Entry::Vacant(entry) = search.distances.entry(source.clone()) {
+            entry.insert(0);
+            queue.push_back((source, 0));
+        }
+    }
+    while let Some((state, distance)) = queue.pop_front() {
+        if is_goal(&state) {
+            search.goal = Some(state);
+            break;
+        }
+        for next in successors(&state) {
+            if let Entry::Vacant(entry) = search.distances.entry(next.clone()) {
+                entry.insert(distance + 1);
+                search.predecessors.insert(next.clone(), state.clone());
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    search
+}
+
+/// Dijkstra's algorithm, for graphs with nonnegative step costs. The successor function returns
+/// each successor with the cost of the step to it.
+pub fn dijkstra<S, C, I>(
+    sources: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(sources, successors, |_| C::default(), is_goal)
+}
+
+/// A* search, which is like Dijkstra's algorithm but explores fewer states by prioritizing those
+/// that seem closer to a goal. The `heuristic` estimates the remaining cost to the nearest goal. It
+/// must be consistent, or the resulting path may not be the shortest: for every step from `a` to
+/// `b`, `heuristic(a) <= cost(a, b) + heuristic(b)`, and it must be zero at goals. This implies that
+/// it never overestimates. Consistency is needed because states are never expanded again once
+/// their distance has been settled.
+///
+/// Only the states expanded before reaching the goal get their final distance, so `distances`
+/// and `path_to` are only meaningful for the goal and the states on its path.
+pub fn astar<S, C, I>(
+    sources: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut distances = HashMap::new();
+    let mut predecessors = HashMap::new();
+    let mut settled = HashMap::new();
+    let mut goal = None;
+    let mut queue = BinaryHeap::new();
+    for source in sources {
+        distances.insert(source.clone(), C::default());
+        queue.push(Queued { priority: heuristic(&source), distance: C::default(), state: source });
+    }
+    while let Some(Queued { distance, state, .. }) = queue.pop() {
+        if settled.contains_key(&state) || distances[&state] < distance {
+            continue;
+        }
+        settled.insert(state.clone(), distance);
+        if is_goal(&state) {
+            goal = Some(state);
+            break;
+        }
+        for (next, cost) in successors(&state) {
+            let next_distance = distance + cost;
+            if distances.get(&next).map_or(true, |&d| next_distance < d) {
+                distances.insert(next.clone(), next_distance);
+                predecessors.insert(next.clone(), state.clone());
+                let priority = next_distance + heuristic(&next);
+                queue.push(Queued { priority, distance: next_distance, state: next });
+            }
+        }
+    }
+    predecessors.retain(|state, _| settled.contains_key(state));
+    Search { distances: settled, predecessors, goal }
+}
+
+/// An entry in the priority queue. Ordered so that the lowest priority is popped first from the
+/// max-heap.
+struct Queued<S, C> {
+    priority: C,
+    distance: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Queued<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S, C: Ord> Eq for Queued<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Queued<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Queued<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+            .then_with(|| self.distance.cmp(&other.distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glam::IVec2;
+
+    use crate::grid::Grid;
+
+    /// Steps from n to n + 1 or 2n.
+    fn double_or_increment(&n: &u32) -> [u32; 2] {
+        [n + 1, 2 * n]
+    }
+
+    #[test]
+    fn test_bfs() {
+        let search = bfs([1], double_or_increment, |&n| n == 10);
+        assert_eq!(search.goal(), Some(&10));
+        assert_eq!(search.goal_distance(), Some(4));
+        assert_eq!(search.goal_path(), Some(vec![1, 2, 4, 5, 10]));
+        assert_eq!(search.predecessor(&1), None);
+
+        let search = bfs([7, 1], double_or_increment, |&n| n == 10);
+        assert_eq!(search.goal_path(), Some(vec![7, 8, 9, 10]));
+
+        let search = bfs([1], |&n: &u32| (n < 5).then_some(n + 1), |_| false);
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.distances().len(), 5);
+        assert_eq!(search.path_to(&6), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let search = dijkstra([1], |&n: &u32| [(n + 1, 1), (2 * n, 3)], |&n| n == 10);
+        assert_eq!(search.goal_distance(), Some(7));
+        assert_eq!(search.goal_path(), Some(vec![1, 2, 3, 4, 5, 10]));
+    }
+
+    #[test]
+    fn test_astar() {
+        let grid = Grid::parse_with("...#\n.#.#\n.#..\n...#\n", |c| Some(c == '#')).unwrap();
+        let goal = IVec2::new(3, 2);
+        let successors = |&pos: &IVec2| {
+            grid.neighbors4(pos).filter(|&next| !grid[next]).map(|next| (next, 1)).collect::<Vec<_>>()
+        };
+        let manhattan = |pos: &IVec2| (goal.x - pos.x).abs() + (goal.y - pos.y).abs();
+        let search = astar([IVec2::ZERO], successors, manhattan, |&pos| pos == goal);
+        assert_eq!(search.goal_distance(), Some(5));
+        assert_eq!(
+            search.goal_path().unwrap(),
+            [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(2, 0), IVec2::new(2, 1), IVec2::new(2, 2), goal]);
+        let search = dijkstra([IVec2::ZERO], successors, |&pos| pos == goal);
+        assert_eq!(search.goal_distance(), Some(5));
+    }
+}
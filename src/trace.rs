@@ -0,0 +1,43 @@
+//! Optional debug output from solutions, such as a drawing of the path taken, which is enabled by
+//! setting the `AOC_TRACE` environment variable to a positive integer N. Solutions that print
+//! intermediate states do so every N steps, where what a step is depends on the puzzle.
+
+use std::env;
+use std::sync::Mutex;
+
+/// The keys for which `every` has already returned a value.
+static TRACED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Returns N if tracing is enabled and this is the first call with this `key`, so that a solution
+/// that is run repeatedly, e.g. with `--bench`, is only traced once. Panics if `AOC_TRACE` is set
+/// to anything other than a positive integer.
+pub fn every(key: &'static str) -> Option<usize> {
+    let value = env::var("AOC_TRACE").ok()?;
+    let every = parse(&value).unwrap_or_else(|| {
+        panic!("AOC_TRACE must be a positive integer, but is {:?}", value)
+    });
+    let mut traced = TRACED.lock().unwrap();
+    if traced.contains(&key) {
+        return None;
+    }
+    traced.push(key);
+    Some(every)
+}
+
+fn parse(value: &str) -> Option<usize> {
+    value.trim().parse().ok().filter(|&every| every > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("5"), Some(5));
+        assert_eq!(parse(" 1\n"), Some(1));
+        assert_eq!(parse("0"), None);
+        assert_eq!(parse("-1"), None);
+        assert_eq!(parse("yes"), None);
+    }
+}